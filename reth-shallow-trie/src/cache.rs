@@ -0,0 +1,369 @@
+// =============================================================================
+// Per-table LRU cache and metrics for shallow trie nodes.
+//
+// The shallow tables exist so hot top-level nodes can get dedicated caching.
+// `ShallowTrieCache` sits above the MDBX cursor: a fixed-capacity LRU of
+// decoded nodes, sized independently from the deep-node block cache, with
+// hit/miss/eviction counters per table so operators can tune
+// `SHALLOW_TRIE_DEPTH` and capacity against real workloads.
+//
+// The storage cache groups nodes by hashed address: each account owns a sub-map
+// of its shallow nodes, so all nodes of one account stay cache-adjacent and
+// evict/invalidate as a unit (cf. nimbus-eth1 prefix grouping). Account-level
+// recency bounds the number of resident accounts. Invalidation hooks the write
+// path (`write_account_trie_updates_split_cached_lru` and its storage sibling):
+// overwriting or deleting a shallow node evicts/refreshes its entry so the cache
+// never drifts from the committed tables.
+// =============================================================================
+
+use alloy_primitives::B256;
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use reth_trie::{BranchNodeCompact, StoredNibbles, StoredNibblesSubKey};
+
+/// Minimal capacity-bounded LRU map. Recency is tracked with a monotonic tick
+/// and an ordered index, so eviction of the least-recently-used entry is
+/// `O(log n)`.
+#[derive(Debug)]
+struct LruMap<K, V> {
+    cap: usize,
+    tick: u64,
+    map: HashMap<K, (V, u64)>,
+    order: BTreeMap<u64, K>,
+}
+
+impl<K: Clone + Eq + Hash + Ord, V: Clone> LruMap<K, V> {
+    fn new(cap: usize) -> Self {
+        Self { cap, tick: 0, map: HashMap::new(), order: BTreeMap::new() }
+    }
+
+    fn touch(&mut self, key: &K, old_tick: u64) -> u64 {
+        self.order.remove(&old_tick);
+        self.tick += 1;
+        self.order.insert(self.tick, key.clone());
+        self.tick
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        let Some((value, tick)) = self.map.get(key).map(|(v, t)| (v.clone(), *t)) else {
+            return None;
+        };
+        let new_tick = self.touch(key, tick);
+        self.map.get_mut(key).unwrap().1 = new_tick;
+        Some(value)
+    }
+
+    /// Insert, evicting the LRU entry if at capacity. Returns whether an
+    /// eviction occurred.
+    fn put(&mut self, key: K, value: V) -> bool {
+        if let Some(entry) = self.map.get_mut(&key) {
+            let old = entry.1;
+            entry.0 = value;
+            let new_tick = self.touch(&key, old);
+            self.map.get_mut(&key).unwrap().1 = new_tick;
+            return false;
+        }
+
+        let mut evicted = false;
+        if self.cap > 0 && self.map.len() >= self.cap {
+            if let Some((&lru_tick, lru_key)) = self.order.iter().next() {
+                let lru_key = lru_key.clone();
+                self.order.remove(&lru_tick);
+                self.map.remove(&lru_key);
+                evicted = true;
+            }
+        }
+
+        self.tick += 1;
+        self.order.insert(self.tick, key.clone());
+        self.map.insert(key, (value, self.tick));
+        evicted
+    }
+
+    fn remove(&mut self, key: &K) {
+        if let Some((_, tick)) = self.map.remove(key) {
+            self.order.remove(&tick);
+        }
+    }
+}
+
+/// One account's resident shallow storage nodes, with its last-access tick.
+#[derive(Debug)]
+struct StorageAccountEntry {
+    nodes: HashMap<StoredNibblesSubKey, BranchNodeCompact>,
+    tick: u64,
+}
+
+/// Account-grouped LRU for shallow storage nodes. Each hashed address owns a
+/// sub-map of its nodes, so a whole account's nodes stay cache-adjacent and are
+/// evicted or invalidated together; recency is tracked per account and the
+/// capacity bounds the number of resident accounts.
+#[derive(Debug)]
+struct StorageGroupedLru {
+    cap_accounts: usize,
+    tick: u64,
+    accounts: HashMap<B256, StorageAccountEntry>,
+    order: BTreeMap<u64, B256>,
+}
+
+impl StorageGroupedLru {
+    fn new(cap_accounts: usize) -> Self {
+        Self { cap_accounts, tick: 0, accounts: HashMap::new(), order: BTreeMap::new() }
+    }
+
+    fn touch(&mut self, address: &B256, old_tick: u64) -> u64 {
+        self.order.remove(&old_tick);
+        self.tick += 1;
+        self.order.insert(self.tick, *address);
+        self.tick
+    }
+
+    fn get(
+        &mut self,
+        address: &B256,
+        key: &StoredNibblesSubKey,
+    ) -> Option<BranchNodeCompact> {
+        let entry = self.accounts.get(address)?;
+        let value = entry.nodes.get(key).cloned()?;
+        let new_tick = self.touch(address, entry.tick);
+        self.accounts.get_mut(address).unwrap().tick = new_tick;
+        Some(value)
+    }
+
+    /// Insert a node under its account, evicting the least-recently-used account
+    /// if a new account would exceed capacity. Returns whether an account was
+    /// evicted.
+    fn put(
+        &mut self,
+        address: B256,
+        key: StoredNibblesSubKey,
+        node: BranchNodeCompact,
+    ) -> bool {
+        if let Some(entry) = self.accounts.get_mut(&address) {
+            entry.nodes.insert(key, node);
+            let old = entry.tick;
+            let new_tick = self.touch(&address, old);
+            self.accounts.get_mut(&address).unwrap().tick = new_tick;
+            return false;
+        }
+
+        let mut evicted = false;
+        if self.cap_accounts > 0 && self.accounts.len() >= self.cap_accounts {
+            if let Some((&lru_tick, &lru_address)) = self.order.iter().next() {
+                self.order.remove(&lru_tick);
+                self.accounts.remove(&lru_address);
+                evicted = true;
+            }
+        }
+
+        self.tick += 1;
+        self.order.insert(self.tick, address);
+        let mut nodes = HashMap::new();
+        nodes.insert(key, node);
+        self.accounts.insert(address, StorageAccountEntry { nodes, tick: self.tick });
+        evicted
+    }
+
+    /// Remove a single node; drop the account group once it is empty.
+    fn remove(&mut self, address: &B256, key: &StoredNibblesSubKey) {
+        if let Some(entry) = self.accounts.get_mut(address) {
+            entry.nodes.remove(key);
+            if entry.nodes.is_empty() {
+                let tick = entry.tick;
+                self.accounts.remove(address);
+                self.order.remove(&tick);
+            }
+        }
+    }
+
+    /// Drop an account's whole group (e.g. on a storage-trie wipe).
+    fn remove_account(&mut self, address: &B256) {
+        if let Some(entry) = self.accounts.remove(address) {
+            self.order.remove(&entry.tick);
+        }
+    }
+}
+
+/// Hit/miss/eviction counters for one table.
+#[derive(Debug, Default)]
+pub struct CacheCounters {
+    /// Lookups served from the cache.
+    pub hits: AtomicU64,
+    /// Lookups that missed and fell through to the DB.
+    pub misses: AtomicU64,
+    /// Entries evicted to stay within capacity.
+    pub evictions: AtomicU64,
+}
+
+/// Immutable snapshot of [`CacheCounters`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl CacheCounters {
+    fn snapshot(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// LRU cache of decoded shallow trie nodes, with independent account and
+/// storage tiers and per-tier metrics.
+#[derive(Debug)]
+pub struct ShallowTrieCache {
+    accounts: Mutex<LruMap<StoredNibbles, BranchNodeCompact>>,
+    account_metrics: CacheCounters,
+    storages: Mutex<StorageGroupedLru>,
+    storage_metrics: CacheCounters,
+}
+
+impl ShallowTrieCache {
+    /// Create a cache holding up to `account_capacity` account nodes and the
+    /// shallow storage nodes of up to `storage_account_capacity` accounts.
+    pub fn new(account_capacity: usize, storage_account_capacity: usize) -> Self {
+        Self {
+            accounts: Mutex::new(LruMap::new(account_capacity)),
+            account_metrics: CacheCounters::default(),
+            storages: Mutex::new(StorageGroupedLru::new(storage_account_capacity)),
+            storage_metrics: CacheCounters::default(),
+        }
+    }
+
+    /// Look up a shallow account node, recording a hit or miss.
+    pub fn get_account(&self, key: &StoredNibbles) -> Option<BranchNodeCompact> {
+        let found = self.accounts.lock().unwrap().get(key);
+        self.record(&self.account_metrics, found.is_some());
+        found
+    }
+
+    /// Insert a shallow account node, recording an eviction if one occurred.
+    pub fn put_account(&self, key: StoredNibbles, node: BranchNodeCompact) {
+        if self.accounts.lock().unwrap().put(key, node) {
+            self.account_metrics.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Evict a shallow account node (hook the write path on overwrite/delete).
+    pub fn invalidate_account(&self, key: &StoredNibbles) {
+        self.accounts.lock().unwrap().remove(key);
+    }
+
+    /// Look up a shallow storage node for `hashed_address`.
+    pub fn get_storage(
+        &self,
+        hashed_address: B256,
+        key: &StoredNibblesSubKey,
+    ) -> Option<BranchNodeCompact> {
+        let found = self.storages.lock().unwrap().get(&hashed_address, key);
+        self.record(&self.storage_metrics, found.is_some());
+        found
+    }
+
+    /// Insert a shallow storage node, grouped under its account.
+    pub fn put_storage(
+        &self,
+        hashed_address: B256,
+        key: StoredNibblesSubKey,
+        node: BranchNodeCompact,
+    ) {
+        if self.storages.lock().unwrap().put(hashed_address, key, node) {
+            self.storage_metrics.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Evict a shallow storage node (hook the write path on overwrite/delete).
+    pub fn invalidate_storage(&self, hashed_address: B256, key: &StoredNibblesSubKey) {
+        self.storages.lock().unwrap().remove(&hashed_address, key);
+    }
+
+    /// Evict an account's whole group of shallow storage nodes (e.g. on a
+    /// storage-trie wipe).
+    pub fn invalidate_storage_account(&self, hashed_address: B256) {
+        self.storages.lock().unwrap().remove_account(&hashed_address);
+    }
+
+    /// Account-tier counters.
+    pub fn account_stats(&self) -> CacheStats {
+        self.account_metrics.snapshot()
+    }
+
+    /// Storage-tier counters.
+    pub fn storage_stats(&self) -> CacheStats {
+        self.storage_metrics.snapshot()
+    }
+
+    fn record(&self, counters: &CacheCounters, hit: bool) {
+        if hit {
+            counters.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            counters.misses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_trie::Nibbles;
+
+    fn node() -> BranchNodeCompact {
+        BranchNodeCompact::new(0b01, 0b01, 0, Vec::default(), None)
+    }
+
+    fn subkey(n: u8) -> StoredNibblesSubKey {
+        StoredNibblesSubKey(Nibbles::from_nibbles([n]))
+    }
+
+    #[test]
+    fn storage_nodes_group_by_account_and_invalidate_individually() {
+        let cache = ShallowTrieCache::new(4, 4);
+        let a = B256::repeat_byte(0xaa);
+        let b = B256::repeat_byte(0xbb);
+
+        cache.put_storage(a, subkey(0x1), node());
+        cache.put_storage(a, subkey(0x2), node());
+        cache.put_storage(b, subkey(0x1), node());
+
+        assert!(cache.get_storage(a, &subkey(0x1)).is_some());
+        assert!(cache.get_storage(a, &subkey(0x2)).is_some());
+        assert!(cache.get_storage(b, &subkey(0x1)).is_some());
+
+        // Invalidating one node leaves the account's other nodes intact.
+        cache.invalidate_storage(a, &subkey(0x1));
+        assert!(cache.get_storage(a, &subkey(0x1)).is_none());
+        assert!(cache.get_storage(a, &subkey(0x2)).is_some());
+
+        // Wiping an account drops all of its nodes as a unit.
+        cache.invalidate_storage_account(a);
+        assert!(cache.get_storage(a, &subkey(0x2)).is_none());
+        assert!(cache.get_storage(b, &subkey(0x1)).is_some());
+    }
+
+    #[test]
+    fn storage_cache_evicts_least_recently_used_account() {
+        let cache = ShallowTrieCache::new(4, 1);
+        let a = B256::repeat_byte(0xaa);
+        let b = B256::repeat_byte(0xbb);
+
+        cache.put_storage(a, subkey(0x1), node());
+        // A second account exceeds the one-account capacity, evicting `a`.
+        cache.put_storage(b, subkey(0x1), node());
+
+        assert!(cache.get_storage(a, &subkey(0x1)).is_none());
+        assert!(cache.get_storage(b, &subkey(0x1)).is_some());
+        assert_eq!(cache.storage_stats().evictions, 1);
+    }
+}
@@ -0,0 +1,217 @@
+// =============================================================================
+// Shared, preloaded in-memory cache of the shallow trie tables.
+//
+// `AccountsTrieShallow`/`StoragesTrieShallow` only hold the hot top-of-trie
+// nodes, yet every state-root or proof run re-seeks the same small set from
+// LMDB. This factory loads the whole shallow account table (and per-address
+// shallow storage) into a sorted structure once, then serves the shallow side
+// of each split cursor from RAM while the deep side stays a DB cursor. The
+// merge with the deep cursor is unchanged.
+//
+// The cache is built from a consistent `DbTx` snapshot and shared via `Arc`
+// across every cursor created from that transaction. Invalidation point:
+// after a writer commits split updates it must rebuild (or swap in a fresh)
+// `CachedShallowTrieCursorFactory`, since the cache reflects the snapshot it
+// was loaded from.
+//
+// Target file (reth): `crates/trie/db/src/trie_cursor.rs`.
+// =============================================================================
+
+use alloy_primitives::B256;
+use std::sync::Arc;
+
+use reth_db_api::{cursor::DbCursorRO, tables, transaction::DbTx, DatabaseError};
+use reth_trie::{
+    trie_cursor::{TrieCursor, TrieCursorFactory},
+    BranchNodeCompact, Nibbles,
+};
+use reth_trie_common::constants::SHALLOW_TRIE_DEPTH;
+
+use crate::{
+    shallow_mem::{ShallowMemAccountCursor, ShallowMemStorageCursor, ShallowTrieMem},
+    trie_cursor::DatabaseAccountTrieCursor,
+};
+
+/// A generic two-way sorted merge over any two [`TrieCursor`]s, using the same
+/// "pending slot, consume the smaller key" shape as the on-disk split cursor.
+/// On equal keys the `a` (shallow) side wins, and point lookups route by depth.
+#[derive(Debug)]
+pub struct TwoWayMergeCursor<A, B> {
+    a: A,
+    b: B,
+    pending_a: Option<(Nibbles, BranchNodeCompact)>,
+    pending_b: Option<(Nibbles, BranchNodeCompact)>,
+    last: Option<MergeSide>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MergeSide {
+    A,
+    B,
+}
+
+impl<A, B> TwoWayMergeCursor<A, B>
+where
+    A: TrieCursor,
+    B: TrieCursor,
+{
+    /// Create a merge cursor over a shallow (`a`) and a deep (`b`) cursor.
+    pub const fn new(a: A, b: B) -> Self {
+        Self { a, b, pending_a: None, pending_b: None, last: None }
+    }
+
+    fn consume_smaller(&mut self) -> Option<(Nibbles, BranchNodeCompact)> {
+        match (&self.pending_a, &self.pending_b) {
+            (Some((ka, _)), Some((kb, _))) => {
+                if ka <= kb {
+                    self.last = Some(MergeSide::A);
+                    self.pending_a.take()
+                } else {
+                    self.last = Some(MergeSide::B);
+                    self.pending_b.take()
+                }
+            }
+            (Some(_), None) => {
+                self.last = Some(MergeSide::A);
+                self.pending_a.take()
+            }
+            (None, Some(_)) => {
+                self.last = Some(MergeSide::B);
+                self.pending_b.take()
+            }
+            (None, None) => {
+                self.last = None;
+                None
+            }
+        }
+    }
+}
+
+impl<A, B> TrieCursor for TwoWayMergeCursor<A, B>
+where
+    A: TrieCursor,
+    B: TrieCursor,
+{
+    fn seek_exact(
+        &mut self,
+        key: Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        self.pending_a = None;
+        self.pending_b = None;
+        if key.len() <= SHALLOW_TRIE_DEPTH {
+            self.last = Some(MergeSide::A);
+            self.a.seek_exact(key)
+        } else {
+            self.last = Some(MergeSide::B);
+            self.b.seek_exact(key)
+        }
+    }
+
+    fn seek(
+        &mut self,
+        key: Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        self.pending_a = self.a.seek(key.clone())?;
+        self.pending_b = self.b.seek(key)?;
+        Ok(self.consume_smaller())
+    }
+
+    fn next(&mut self) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        match self.last {
+            Some(MergeSide::A) => self.pending_a = self.a.next()?,
+            Some(MergeSide::B) => self.pending_b = self.b.next()?,
+            None => {
+                if self.pending_a.is_none() {
+                    self.pending_a = self.a.next()?;
+                }
+                if self.pending_b.is_none() {
+                    self.pending_b = self.b.next()?;
+                }
+            }
+        }
+        Ok(self.consume_smaller())
+    }
+
+    fn current(&mut self) -> Result<Option<Nibbles>, DatabaseError> {
+        match self.last {
+            Some(MergeSide::A) => self.a.current(),
+            Some(MergeSide::B) => self.b.current(),
+            None => Ok(None),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.a.reset();
+        self.b.reset();
+        self.pending_a = None;
+        self.pending_b = None;
+        self.last = None;
+    }
+}
+
+/// Cursor factory serving the shallow side of every split cursor from a shared,
+/// preloaded [`ShallowTrieMem`] and the deep side from the database.
+#[derive(Debug, Clone)]
+pub struct CachedShallowTrieCursorFactory<T> {
+    tx: T,
+    cache: Arc<ShallowTrieMem>,
+}
+
+impl<T> CachedShallowTrieCursorFactory<T> {
+    /// Wrap a transaction with an already-loaded shallow cache (shareable).
+    pub const fn new(tx: T, cache: Arc<ShallowTrieMem>) -> Self {
+        Self { tx, cache }
+    }
+}
+
+impl<TX> CachedShallowTrieCursorFactory<&TX>
+where
+    TX: DbTx,
+{
+    /// Load the shallow cache from `tx` and wrap it for sharing.
+    pub fn load(tx: &TX) -> Result<CachedShallowTrieCursorFactory<&TX>, DatabaseError> {
+        let cache = Arc::new(ShallowTrieMem::load(tx)?);
+        Ok(CachedShallowTrieCursorFactory::new(tx, cache))
+    }
+}
+
+impl<TX> TrieCursorFactory for CachedShallowTrieCursorFactory<&TX>
+where
+    TX: DbTx,
+{
+    type AccountTrieCursor<'a>
+        = TwoWayMergeCursor<
+            ShallowMemAccountCursor<'a>,
+            DatabaseAccountTrieCursor<<TX as DbTx>::Cursor<tables::AccountsTrie>>,
+        >
+    where
+        Self: 'a;
+
+    type StorageTrieCursor<'a>
+        = TwoWayMergeCursor<
+            ShallowMemStorageCursor<'a>,
+            crate::trie_cursor::DatabaseStorageTrieCursor<
+                <TX as DbTx>::DupCursor<tables::StoragesTrie>,
+            >,
+        >
+    where
+        Self: 'a;
+
+    fn account_trie_cursor(&self) -> Result<Self::AccountTrieCursor<'_>, DatabaseError> {
+        let shallow = self.cache.account_cursor();
+        let deep = DatabaseAccountTrieCursor::new(self.tx.cursor_read::<tables::AccountsTrie>()?);
+        Ok(TwoWayMergeCursor::new(shallow, deep))
+    }
+
+    fn storage_trie_cursor(
+        &self,
+        hashed_address: B256,
+    ) -> Result<Self::StorageTrieCursor<'_>, DatabaseError> {
+        let shallow = self.cache.storage_cursor(hashed_address);
+        let deep = crate::trie_cursor::DatabaseStorageTrieCursor::new(
+            self.tx.cursor_dup_read::<tables::StoragesTrie>()?,
+            hashed_address,
+        );
+        Ok(TwoWayMergeCursor::new(shallow, deep))
+    }
+}
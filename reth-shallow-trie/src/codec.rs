@@ -0,0 +1,279 @@
+// =============================================================================
+// Pluggable per-table encoding/compression for BranchNodeCompact.
+//
+// A fuel-core-style `TableWithStructure` layer so the trie tables can declare
+// their own value codec instead of hard-wiring one serialization. Because
+// shallow nodes are read on nearly every state access while deep nodes dominate
+// on-disk volume, an operator can give `AccountsTrie`/`StoragesTrie` a
+// space-optimized codec while `*TrieShallow` uses a decode-fast one. The trie
+// history layer (`crate::history`) stores its pre-image values through these
+// codecs; the trie tables themselves and the split cursors keep the node's own
+// `Compact` encoding and do not go through a codec.
+// =============================================================================
+
+use alloy_primitives::{Bytes, B256};
+
+use reth_db_api::{tables, DatabaseError};
+use reth_trie::BranchNodeCompact;
+
+/// Per-table value codec for trie nodes. Methods take `&self` so a concrete
+/// codec can be stored behind a trait object and selected per table at runtime
+/// (see [`TrieCodecRegistry`]); a caller encodes/decodes through the registry
+/// without knowing which codec backs a given tier.
+pub trait TrieNodeCodec: std::fmt::Debug {
+    /// Encode a node to its on-disk representation.
+    fn encode(&self, node: &BranchNodeCompact) -> Bytes;
+    /// Decode a node from its on-disk representation.
+    fn decode(&self, bytes: &[u8]) -> Result<BranchNodeCompact, DatabaseError>;
+}
+
+/// The plain codec: the node's canonical reth `Compact` encoding. Fast to
+/// decode; the default for the hot shallow tables.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlainTrieNodeCodec;
+
+impl TrieNodeCodec for PlainTrieNodeCodec {
+    fn encode(&self, node: &BranchNodeCompact) -> Bytes {
+        use reth_codecs::Compact;
+        let mut buf = Vec::new();
+        node.to_compact(&mut buf);
+        buf.into()
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<BranchNodeCompact, DatabaseError> {
+        use reth_codecs::Compact;
+        let (node, _) = BranchNodeCompact::from_compact(bytes, bytes.len());
+        Ok(node)
+    }
+}
+
+/// The compact codec: bit-packs the three masks and stores only the non-empty
+/// hash slots, trading a little CPU for less on-disk volume. Suited to the deep
+/// tables, which dominate space.
+///
+/// Layout: `state_mask(2) | tree_mask(2) | hash_mask(2) | flags(1)` followed by
+/// the optional root hash (32) and then `popcount(hash_mask)` hashes (32 each).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompactTrieNodeCodec;
+
+const HAS_ROOT_HASH: u8 = 0b0000_0001;
+
+impl TrieNodeCodec for CompactTrieNodeCodec {
+    fn encode(&self, node: &BranchNodeCompact) -> Bytes {
+        let mut buf = Vec::with_capacity(7 + node.hashes.len() * 32);
+        buf.extend_from_slice(&node.state_mask.get().to_le_bytes());
+        buf.extend_from_slice(&node.tree_mask.get().to_le_bytes());
+        buf.extend_from_slice(&node.hash_mask.get().to_le_bytes());
+
+        let flags = if node.root_hash.is_some() { HAS_ROOT_HASH } else { 0 };
+        buf.push(flags);
+        if let Some(root) = node.root_hash {
+            buf.extend_from_slice(root.as_slice());
+        }
+        // `hashes` already holds exactly the set hash_mask slots, so empty
+        // slots are never written.
+        for hash in node.hashes.iter() {
+            buf.extend_from_slice(hash.as_slice());
+        }
+        buf.into()
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<BranchNodeCompact, DatabaseError> {
+        let invalid = || DatabaseError::Decode;
+        if bytes.len() < 7 {
+            return Err(invalid());
+        }
+        let state_mask = u16::from_le_bytes([bytes[0], bytes[1]]);
+        let tree_mask = u16::from_le_bytes([bytes[2], bytes[3]]);
+        let hash_mask = u16::from_le_bytes([bytes[4], bytes[5]]);
+        let flags = bytes[6];
+
+        let mut offset = 7;
+        let root_hash = if flags & HAS_ROOT_HASH != 0 {
+            let end = offset + 32;
+            let root = B256::from_slice(bytes.get(offset..end).ok_or_else(invalid)?);
+            offset = end;
+            Some(root)
+        } else {
+            None
+        };
+
+        let num_hashes = hash_mask.count_ones() as usize;
+        let mut hashes = Vec::with_capacity(num_hashes);
+        for _ in 0..num_hashes {
+            let end = offset + 32;
+            hashes.push(B256::from_slice(bytes.get(offset..end).ok_or_else(invalid)?));
+            offset = end;
+        }
+
+        Ok(BranchNodeCompact::new(state_mask, tree_mask, hash_mask, hashes, root_hash))
+    }
+}
+
+/// The `TableWithStructure`-style association between a trie table and the
+/// codec for its node values, so a caller can resolve the right codec from the
+/// table type alone (see [`encode_for`]/[`decode_for`]). The split cursors do
+/// not read or write through this binding.
+pub trait TrieTableCodec {
+    /// Codec used for this table's `BranchNodeCompact` values.
+    type Codec: TrieNodeCodec + Default;
+}
+
+// Hot shallow tables favour decode speed; the volume-dominant deep tables
+// favour on-disk size.
+impl TrieTableCodec for tables::AccountsTrieShallow {
+    type Codec = PlainTrieNodeCodec;
+}
+impl TrieTableCodec for tables::StoragesTrieShallow {
+    type Codec = PlainTrieNodeCodec;
+}
+impl TrieTableCodec for tables::AccountsTrie {
+    type Codec = CompactTrieNodeCodec;
+}
+impl TrieTableCodec for tables::StoragesTrie {
+    type Codec = CompactTrieNodeCodec;
+}
+
+/// Encode a node with the codec bound to table `T`.
+pub fn encode_for<T: TrieTableCodec>(node: &BranchNodeCompact) -> Bytes {
+    T::Codec::default().encode(node)
+}
+
+/// Decode a node value from table `T` using that table's bound codec.
+pub fn decode_for<T: TrieTableCodec>(
+    bytes: &[u8],
+) -> Result<BranchNodeCompact, DatabaseError> {
+    T::Codec::default().decode(bytes)
+}
+
+/// Binds a codec to each trie table tier so node values can be encoded and
+/// decoded without hard-wiring one serialization. The shallow tier
+/// (`*TrieShallow`) and the deep tier (`AccountsTrie`/`StoragesTrie`) each
+/// carry their own codec. The history layer encodes and decodes its pre-images
+/// through it; the split cursors and the write routers do not.
+#[derive(Debug)]
+pub struct TrieCodecRegistry {
+    shallow: Box<dyn TrieNodeCodec + Send + Sync>,
+    deep: Box<dyn TrieNodeCodec + Send + Sync>,
+}
+
+impl Default for TrieCodecRegistry {
+    /// Decode-fast plain codec for the hot shallow tables; space-optimized
+    /// compact codec for the volume-dominant deep tables.
+    fn default() -> Self {
+        Self {
+            shallow: Box::new(PlainTrieNodeCodec),
+            deep: Box::new(CompactTrieNodeCodec),
+        }
+    }
+}
+
+impl TrieCodecRegistry {
+    /// Create a registry with explicit codecs for each tier.
+    pub fn new(
+        shallow: impl TrieNodeCodec + Send + Sync + 'static,
+        deep: impl TrieNodeCodec + Send + Sync + 'static,
+    ) -> Self {
+        Self { shallow: Box::new(shallow), deep: Box::new(deep) }
+    }
+
+    /// The codec bound to the given tier (`true` = shallow).
+    pub fn codec(&self, is_shallow: bool) -> &(dyn TrieNodeCodec + Send + Sync) {
+        if is_shallow {
+            self.shallow.as_ref()
+        } else {
+            self.deep.as_ref()
+        }
+    }
+
+    /// Encode a node for the given tier.
+    pub fn encode(&self, is_shallow: bool, node: &BranchNodeCompact) -> Bytes {
+        self.codec(is_shallow).encode(node)
+    }
+
+    /// Decode a node from the given tier.
+    pub fn decode(
+        &self,
+        is_shallow: bool,
+        bytes: &[u8],
+    ) -> Result<BranchNodeCompact, DatabaseError> {
+        self.codec(is_shallow).decode(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::B256;
+    use reth_trie::BranchNodeCompact;
+
+    fn sample_node() -> BranchNodeCompact {
+        BranchNodeCompact::new(
+            0b1011,
+            0b0010,
+            0b1001,
+            vec![B256::repeat_byte(0x11), B256::repeat_byte(0x22)],
+            Some(B256::repeat_byte(0x33)),
+        )
+    }
+
+    #[test]
+    fn plain_codec_round_trips() {
+        let codec = PlainTrieNodeCodec;
+        let node = sample_node();
+        let decoded = codec.decode(&codec.encode(&node)).unwrap();
+        assert_eq!(decoded, node);
+    }
+
+    #[test]
+    fn compact_codec_round_trips() {
+        let codec = CompactTrieNodeCodec;
+        let node = sample_node();
+        let decoded = codec.decode(&codec.encode(&node)).unwrap();
+        assert_eq!(decoded, node);
+    }
+
+    #[test]
+    fn compact_codec_round_trips_without_root_hash() {
+        let codec = CompactTrieNodeCodec;
+        let node = BranchNodeCompact::new(0b0001, 0, 0, vec![], None);
+        let decoded = codec.decode(&codec.encode(&node)).unwrap();
+        assert_eq!(decoded, node);
+    }
+
+    #[test]
+    fn table_bound_codec_round_trips() {
+        let node = sample_node();
+
+        // Each table resolves its own codec; values round-trip through it.
+        let shallow = encode_for::<tables::AccountsTrieShallow>(&node);
+        assert_eq!(decode_for::<tables::AccountsTrieShallow>(&shallow).unwrap(), node);
+
+        let deep = encode_for::<tables::AccountsTrie>(&node);
+        assert_eq!(decode_for::<tables::AccountsTrie>(&deep).unwrap(), node);
+
+        // Storage tables mirror the account tables' tier choices.
+        let storage_shallow = encode_for::<tables::StoragesTrieShallow>(&node);
+        assert_eq!(decode_for::<tables::StoragesTrieShallow>(&storage_shallow).unwrap(), node);
+        let storage_deep = encode_for::<tables::StoragesTrie>(&node);
+        assert_eq!(decode_for::<tables::StoragesTrie>(&storage_deep).unwrap(), node);
+
+        // Shallow (plain) and deep (compact) encodings genuinely differ.
+        assert_ne!(shallow, deep);
+    }
+
+    #[test]
+    fn registry_routes_each_tier_through_its_codec() {
+        let registry = TrieCodecRegistry::default();
+        let node = sample_node();
+
+        // Shallow tier uses the plain codec, deep tier the compact one; their
+        // encodings differ but both decode back to the original node.
+        let shallow_bytes = registry.encode(true, &node);
+        let deep_bytes = registry.encode(false, &node);
+        assert_ne!(shallow_bytes, deep_bytes);
+
+        assert_eq!(registry.decode(true, &shallow_bytes).unwrap(), node);
+        assert_eq!(registry.decode(false, &deep_bytes).unwrap(), node);
+    }
+}
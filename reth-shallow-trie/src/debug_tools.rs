@@ -0,0 +1,99 @@
+// =============================================================================
+// Trie-cursor diff tool.
+//
+// Walk two cursors in lockstep sorted-merge order and report the first key at
+// which they disagree. Invaluable for debugging mismatches between the
+// split-table layout and a reference single-table trie, or between two
+// database snapshots.
+// =============================================================================
+
+use reth_db_api::DatabaseError;
+use reth_trie::{trie_cursor::TrieCursor, BranchNodeCompact, Nibbles};
+
+/// The first point at which two cursors diverge.
+///
+/// `a`/`b` are the node each side holds at `path`; `None` means the key is
+/// absent on that side. The three cases are: present only in `a`
+/// (`a.is_some() && b.is_none()`), present only in `b`, or present in both with
+/// differing [`BranchNodeCompact`] payloads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffPoint {
+    /// The diverging path.
+    pub path: Nibbles,
+    /// Length of the common nibble prefix of the two candidate keys at the
+    /// divergence (equal to `path.len()` when both sides hold the same key).
+    pub common_prefix_depth: usize,
+    /// Node held by the first cursor at `path`, if any.
+    pub a: Option<BranchNodeCompact>,
+    /// Node held by the second cursor at `path`, if any.
+    pub b: Option<BranchNodeCompact>,
+}
+
+/// Number of leading nibbles shared by two paths.
+fn common_prefix_depth(a: &Nibbles, b: &Nibbles) -> usize {
+    a.as_slice().iter().zip(b.as_slice()).take_while(|(x, y)| x == y).count()
+}
+
+/// Walk `a` and `b` in ascending key order and return the first divergence,
+/// or `None` if both cursors yield identical `(key, node)` streams.
+pub fn find_first_diff<A, B>(a: &mut A, b: &mut B) -> Result<Option<DiffPoint>, DatabaseError>
+where
+    A: TrieCursor,
+    B: TrieCursor,
+{
+    let mut ca = a.seek(Nibbles::default())?;
+    let mut cb = b.seek(Nibbles::default())?;
+
+    loop {
+        match (&ca, &cb) {
+            (None, None) => return Ok(None),
+            (Some((ka, va)), None) => {
+                return Ok(Some(DiffPoint {
+                    common_prefix_depth: ka.len(),
+                    path: ka.clone(),
+                    a: Some(va.clone()),
+                    b: None,
+                }));
+            }
+            (None, Some((kb, vb))) => {
+                return Ok(Some(DiffPoint {
+                    common_prefix_depth: kb.len(),
+                    path: kb.clone(),
+                    a: None,
+                    b: Some(vb.clone()),
+                }));
+            }
+            (Some((ka, va)), Some((kb, vb))) => {
+                if ka == kb {
+                    if va != vb {
+                        return Ok(Some(DiffPoint {
+                            common_prefix_depth: ka.len(),
+                            path: ka.clone(),
+                            a: Some(va.clone()),
+                            b: Some(vb.clone()),
+                        }));
+                    }
+                    // Equal key and payload — advance both.
+                    ca = a.next()?;
+                    cb = b.next()?;
+                } else if ka < kb {
+                    // `ka` is absent on the `b` side.
+                    return Ok(Some(DiffPoint {
+                        common_prefix_depth: common_prefix_depth(ka, kb),
+                        path: ka.clone(),
+                        a: Some(va.clone()),
+                        b: None,
+                    }));
+                } else {
+                    // `kb` is absent on the `a` side.
+                    return Ok(Some(DiffPoint {
+                        common_prefix_depth: common_prefix_depth(ka, kb),
+                        path: kb.clone(),
+                        a: None,
+                        b: Some(vb.clone()),
+                    }));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,243 @@
+// =============================================================================
+// In-memory overlay on top of the split shallow/deep trie cursors.
+//
+// Wraps any `TrieCursorFactory` (itself already the two-table shallow/deep
+// merge) plus a sorted overlay of pending updates, and returns cursors that
+// perform a three-way sorted merge so state-root and proof code can run
+// against uncommitted updates without writing them first.
+//
+// Target file (reth): `crates/trie/trie/src/trie_cursor/in_memory.rs`.
+// =============================================================================
+
+use alloy_primitives::B256;
+
+use reth_db_api::DatabaseError;
+use reth_trie::{
+    trie_cursor::{TrieCursor, TrieCursorFactory},
+    updates::{StorageTrieUpdatesSorted, TrieUpdatesSorted},
+    BranchNodeCompact, Nibbles,
+};
+
+/// An overlay entry: a node inserted (`Some`) or removed (`None`) at a path.
+type OverlayEntry = (Nibbles, Option<BranchNodeCompact>);
+
+/// Factory wrapping an inner [`TrieCursorFactory`] with a pending-update
+/// overlay. Cursors it creates merge the inner (DB) stream with the overlay.
+#[derive(Debug)]
+pub struct InMemorySplitTrieCursorFactory<'a, F> {
+    inner: F,
+    overlay: &'a TrieUpdatesSorted,
+}
+
+impl<'a, F> InMemorySplitTrieCursorFactory<'a, F> {
+    /// Create a new overlay factory.
+    pub const fn new(inner: F, overlay: &'a TrieUpdatesSorted) -> Self {
+        Self { inner, overlay }
+    }
+}
+
+impl<'a, F> TrieCursorFactory for InMemorySplitTrieCursorFactory<'a, F>
+where
+    F: TrieCursorFactory,
+{
+    type AccountTrieCursor<'b>
+        = InMemorySplitTrieCursor<'a, F::AccountTrieCursor<'b>>
+    where
+        Self: 'b;
+
+    type StorageTrieCursor<'b>
+        = InMemorySplitTrieCursor<'a, F::StorageTrieCursor<'b>>
+    where
+        Self: 'b;
+
+    fn account_trie_cursor(&self) -> Result<Self::AccountTrieCursor<'_>, DatabaseError> {
+        Ok(InMemorySplitTrieCursor::new(
+            self.inner.account_trie_cursor()?,
+            &self.overlay.account_nodes,
+            false,
+        ))
+    }
+
+    fn storage_trie_cursor(
+        &self,
+        hashed_address: B256,
+    ) -> Result<Self::StorageTrieCursor<'_>, DatabaseError> {
+        let (nodes, wiped) = match self.overlay.storage_tries.get(&hashed_address) {
+            Some(storage) => (storage.storage_nodes.as_slice(), storage.is_deleted()),
+            None => (EMPTY_OVERLAY, false),
+        };
+        Ok(InMemorySplitTrieCursor::new(
+            self.inner.storage_trie_cursor(hashed_address)?,
+            nodes,
+            wiped,
+        ))
+    }
+}
+
+const EMPTY_OVERLAY: &[OverlayEntry] = &[];
+
+/// Factory wrapper that layers pending trie updates over the shallow/deep
+/// `DatabaseTrieCursorFactory`. Alias for [`InMemorySplitTrieCursorFactory`]:
+/// the overlay plus the split DB cursor is exactly the three-way (shallow,
+/// deep, overlay) merge callers want for computing intermediate state roots
+/// during block execution without flushing trie nodes first.
+pub type InMemoryTrieCursorFactory<'a, F> = InMemorySplitTrieCursorFactory<'a, F>;
+
+/// Cursor doing the three-way (shallow, deep, overlay) sorted merge. Naming
+/// alias for [`InMemorySplitTrieCursor`]; strictly ascending, de-duplicated
+/// output across all three sources (overlay shadows DB at equal keys, removals
+/// hide rows). The alias names the same type and adds no behavior of its own.
+pub type InMemoryTrieCursor<'a, C> = InMemorySplitTrieCursor<'a, C>;
+
+/// Cursor performing a three-way sorted merge: the DB side (`db`, itself the
+/// shallow+deep merge) and a sorted `overlay` slice. Overlay entries shadow DB
+/// entries at the same key; an overlay removal (`None`) hides the DB row.
+#[derive(Debug)]
+pub struct InMemorySplitTrieCursor<'a, C> {
+    db: C,
+    /// Buffered DB head; `None` once the DB stream is exhausted.
+    db_head: Option<(Nibbles, BranchNodeCompact)>,
+    db_done: bool,
+    overlay: &'a [OverlayEntry],
+    overlay_idx: usize,
+    /// When the whole storage trie is wiped, the DB side is treated as empty.
+    wiped: bool,
+    last: Option<Nibbles>,
+}
+
+impl<'a, C> InMemorySplitTrieCursor<'a, C>
+where
+    C: TrieCursor,
+{
+    fn new(db: C, overlay: &'a [OverlayEntry], wiped: bool) -> Self {
+        Self { db, db_head: None, db_done: wiped, overlay, overlay_idx: 0, wiped, last: None }
+    }
+
+    /// Return the current DB head without consuming it.
+    fn db_peek(&self) -> Option<&(Nibbles, BranchNodeCompact)> {
+        self.db_head.as_ref()
+    }
+
+    /// Return the current DB head and refill the buffer with the next row.
+    fn db_advance(&mut self) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        let current = self.db_head.take();
+        if !self.db_done {
+            self.db_head = self.db.next()?;
+            if self.db_head.is_none() {
+                self.db_done = true;
+            }
+        }
+        Ok(current)
+    }
+
+    /// Merge the overlay and DB heads, skipping overlay removals.
+    fn pick(&mut self) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        loop {
+            let overlay_head = self.overlay.get(self.overlay_idx);
+            let result = match (overlay_head, self.db_peek()) {
+                (None, None) => None,
+                (Some((ok, ov)), None) => {
+                    let entry = (ok.clone(), ov.clone());
+                    self.overlay_idx += 1;
+                    Some(entry)
+                }
+                (None, Some(_)) => self.db_advance()?.map(|(k, v)| (k, Some(v))),
+                (Some((ok, ov)), Some((dk, _))) => {
+                    if ok < dk {
+                        let entry = (ok.clone(), ov.clone());
+                        self.overlay_idx += 1;
+                        Some(entry)
+                    } else if ok == dk {
+                        // Overlay shadows the DB row at the same key.
+                        let entry = (ok.clone(), ov.clone());
+                        self.overlay_idx += 1;
+                        let _ = self.db_advance()?;
+                        Some(entry)
+                    } else {
+                        self.db_advance()?.map(|(k, v)| (k, Some(v)))
+                    }
+                }
+            };
+
+            match result {
+                // Removal marker: skip it and continue merging.
+                Some((_, None)) => continue,
+                Some((k, Some(v))) => {
+                    self.last = Some(k.clone());
+                    return Ok(Some((k, v)));
+                }
+                None => {
+                    self.last = None;
+                    return Ok(None);
+                }
+            }
+        }
+    }
+}
+
+impl<C> TrieCursor for InMemorySplitTrieCursor<'_, C>
+where
+    C: TrieCursor,
+{
+    fn seek_exact(
+        &mut self,
+        key: Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        // Reposition the three-way merge at the first entry >= key, exactly as
+        // `seek` does, so a following `next()`/`current()` resumes from the
+        // seeked key rather than from wherever a prior `seek` left the merge.
+        self.overlay_idx = self.overlay.partition_point(|(k, _)| k < &key);
+        self.db_head = if self.wiped { None } else { self.db.seek(key.clone())? };
+        self.db_done = self.db_head.is_none();
+
+        // An overlay entry at the exact key shadows the DB row (a removal marker
+        // hides it); consume both sides at that key so `next()` moves past it.
+        if let Some((ok, ov)) = self.overlay.get(self.overlay_idx).filter(|(k, _)| *k == key) {
+            let found = ov.clone().map(|node| (ok.clone(), node));
+            self.overlay_idx += 1;
+            if self.db_peek().map_or(false, |e| e.0 == key) {
+                let _ = self.db_advance()?;
+            }
+            self.last = found.as_ref().map(|(k, _)| k.clone());
+            return Ok(found);
+        }
+
+        // Otherwise the DB side may hold the key exactly.
+        if self.db_peek().map_or(false, |e| e.0 == key) {
+            let entry = self.db_advance()?;
+            self.last = entry.as_ref().map(|(k, _)| k.clone());
+            return Ok(entry);
+        }
+
+        // Absent: no current position, but the merge heads stay positioned so a
+        // later `next()` still yields the successors of `key` in order.
+        self.last = None;
+        Ok(None)
+    }
+
+    fn seek(
+        &mut self,
+        key: Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        self.overlay_idx = self.overlay.partition_point(|(k, _)| k < &key);
+        self.db_head = if self.wiped { None } else { self.db.seek(key)? };
+        self.db_done = self.db_head.is_none();
+        self.pick()
+    }
+
+    fn next(&mut self) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        self.pick()
+    }
+
+    fn current(&mut self) -> Result<Option<Nibbles>, DatabaseError> {
+        Ok(self.last.clone())
+    }
+
+    fn reset(&mut self) {
+        self.db.reset();
+        self.db_head = None;
+        self.db_done = self.wiped;
+        self.overlay_idx = 0;
+        self.last = None;
+    }
+}
@@ -0,0 +1,62 @@
+// =============================================================================
+// Profiling for the shallow/deep split boundary.
+//
+// The optimal `shallow_trie_depth` depends on trie shape and workload. This
+// opt-in instrumentation tallies, per node-path length, how many nodes land at
+// that depth and how many encoded bytes they occupy, so operators can pick a
+// boundary that keeps the shallow set within a target RAM budget.
+// =============================================================================
+
+use alloy_rlp::Encodable;
+use reth_trie::BranchNodeCompact;
+use std::collections::BTreeMap;
+
+/// Count and encoded byte size of the nodes observed at a given path length.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DepthBucket {
+    /// Number of nodes observed at this path length.
+    pub count: u64,
+    /// Total encoded byte size of those nodes.
+    pub encoded_bytes: u64,
+}
+
+/// Histogram of node-path-length -> `(count, encoded-byte-size)`.
+///
+/// Populate it from the write path (see [`DepthHistogram::observe`]) and read
+/// [`DepthHistogram::shallow_budget`] to see how much RAM a candidate boundary
+/// would require.
+#[derive(Debug, Default, Clone)]
+pub struct DepthHistogram {
+    buckets: BTreeMap<usize, DepthBucket>,
+}
+
+impl DepthHistogram {
+    /// Create an empty histogram.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a node of path length `depth` and its encoded size.
+    pub fn observe(&mut self, depth: usize, node: &BranchNodeCompact) {
+        let bucket = self.buckets.entry(depth).or_default();
+        bucket.count += 1;
+        bucket.encoded_bytes += node.length() as u64;
+    }
+
+    /// Per-depth buckets in ascending depth order.
+    pub fn buckets(&self) -> impl Iterator<Item = (usize, DepthBucket)> + '_ {
+        self.buckets.iter().map(|(d, b)| (*d, *b))
+    }
+
+    /// Aggregate `(count, encoded_bytes)` for all nodes with path length
+    /// `<= depth` — i.e. what the shallow table would hold for that boundary.
+    pub fn shallow_budget(&self, depth: usize) -> DepthBucket {
+        self.buckets
+            .range(..=depth)
+            .fold(DepthBucket::default(), |mut acc, (_, b)| {
+                acc.count += b.count;
+                acc.encoded_bytes += b.encoded_bytes;
+                acc
+            })
+    }
+}
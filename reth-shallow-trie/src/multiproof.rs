@@ -0,0 +1,175 @@
+// =============================================================================
+// Multiproof generation over the shallow/deep split layout.
+//
+// Given a set of target account and storage keys, walk the split tables to
+// collect every `BranchNodeCompact` on each target's path and emit an ordered
+// proof subtree per target. Because the first `SHALLOW_TRIE_DEPTH` nibbles of
+// every path live in the shallow table, the shared shallow-prefix nodes are
+// gathered once and reused across all targets in the same block.
+//
+// Target file (reth): `crates/trie/trie/src/proof/mod.rs`, in the spirit of
+// the `trie/multiproof` module.
+// =============================================================================
+
+use alloy_primitives::{Bytes, B256};
+use alloy_rlp::Encodable;
+use std::collections::{BTreeMap, HashMap};
+
+use reth_db_api::DatabaseError;
+use reth_trie::{
+    trie_cursor::{TrieCursor, TrieCursorFactory},
+    BranchNodeCompact, Nibbles,
+};
+use reth_trie_common::constants::SHALLOW_TRIE_DEPTH;
+
+/// A single node on a target's proof path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofNode {
+    /// Nibble path of the node.
+    pub path: Nibbles,
+    /// The branch node itself.
+    pub node: BranchNodeCompact,
+    /// RLP encoding of the node.
+    pub encoded: Bytes,
+}
+
+impl ProofNode {
+    fn new(path: Nibbles, node: BranchNodeCompact) -> Self {
+        let mut encoded = Vec::new();
+        node.encode(&mut encoded);
+        Self { path, node, encoded: encoded.into() }
+    }
+}
+
+/// Ordered proof nodes for each requested target, keyed by target path.
+pub type Multiproof = BTreeMap<Nibbles, Vec<ProofNode>>;
+
+/// Builds multiproofs from a split-table [`TrieCursorFactory`], sharing the
+/// common shallow prefix across every target in a batch.
+#[derive(Debug)]
+pub struct ShallowSplitMultiproofBuilder<F> {
+    factory: F,
+}
+
+impl<F> ShallowSplitMultiproofBuilder<F>
+where
+    F: TrieCursorFactory,
+{
+    /// Create a new builder over the given cursor factory.
+    pub const fn new(factory: F) -> Self {
+        Self { factory }
+    }
+
+    /// Build a multiproof for a set of target account paths.
+    pub fn account_multiproof(
+        &self,
+        targets: impl IntoIterator<Item = Nibbles>,
+    ) -> Result<Multiproof, DatabaseError> {
+        let mut cursor = self.factory.account_trie_cursor()?;
+        self.multiproof(&mut cursor, targets)
+    }
+
+    /// Build a multiproof for a set of target storage slot paths under one
+    /// hashed address.
+    pub fn storage_multiproof(
+        &self,
+        hashed_address: B256,
+        targets: impl IntoIterator<Item = Nibbles>,
+    ) -> Result<Multiproof, DatabaseError> {
+        let mut cursor = self.factory.storage_trie_cursor(hashed_address)?;
+        self.multiproof(&mut cursor, targets)
+    }
+
+    /// Shared walk: for each target gather the nodes at every prefix of its
+    /// path, caching shallow-prefix lookups so they cost a single seek across
+    /// the whole batch. The resulting per-target list is sorted by path so
+    /// downstream verifiers receive nodes in ascending order.
+    fn multiproof<C: TrieCursor>(
+        &self,
+        cursor: &mut C,
+        targets: impl IntoIterator<Item = Nibbles>,
+    ) -> Result<Multiproof, DatabaseError> {
+        // Cache of shallow-prefix lookups shared across targets: path -> node
+        // present (Some) or proven absent (None).
+        let mut shallow_cache: HashMap<Nibbles, Option<BranchNodeCompact>> = HashMap::new();
+        let mut proof = Multiproof::new();
+
+        for target in targets {
+            let mut nodes: BTreeMap<Nibbles, BranchNodeCompact> = BTreeMap::new();
+
+            for depth in 0..=target.len() {
+                let prefix = target.slice(..depth);
+
+                let node = if depth <= SHALLOW_TRIE_DEPTH {
+                    // Reuse or populate the shared shallow cache.
+                    match shallow_cache.get(&prefix) {
+                        Some(cached) => cached.clone(),
+                        None => {
+                            let found =
+                                cursor.seek_exact(prefix.clone())?.map(|(_, node)| node);
+                            shallow_cache.insert(prefix.clone(), found.clone());
+                            found
+                        }
+                    }
+                } else {
+                    cursor.seek_exact(prefix.clone())?.map(|(_, node)| node)
+                };
+
+                if let Some(node) = node {
+                    nodes.insert(prefix, node);
+                }
+            }
+
+            let ordered =
+                nodes.into_iter().map(|(path, node)| ProofNode::new(path, node)).collect();
+            proof.insert(target, ordered);
+        }
+
+        Ok(proof)
+    }
+
+    /// Build a single compact [`StorageProof`] covering a set of account targets
+    /// and per-account storage slots. Nodes are gathered from the shallow and
+    /// deep tables, de-duplicated by path, and emitted in ascending path order
+    /// so a stateless verifier can reconstruct the partial trie and recompute
+    /// the root. Because the hot top nodes come from the shallow table, the
+    /// shared prefix is read once per account set.
+    pub fn build_storage_proof(
+        &self,
+        account_targets: impl IntoIterator<Item = Nibbles>,
+        storage_targets: impl IntoIterator<Item = (B256, Vec<Nibbles>)>,
+    ) -> Result<StorageProof, DatabaseError> {
+        let account = dedup(self.account_multiproof(account_targets)?);
+
+        let mut storages = BTreeMap::new();
+        for (hashed_address, slots) in storage_targets {
+            let nodes = dedup(self.storage_multiproof(hashed_address, slots)?);
+            storages.insert(hashed_address, nodes);
+        }
+
+        Ok(StorageProof { account, storages })
+    }
+}
+
+/// Collapse a per-target [`Multiproof`] into one path-sorted, de-duplicated
+/// node list (shared prefix nodes appear once).
+fn dedup(proof: Multiproof) -> Vec<ProofNode> {
+    let mut by_path: BTreeMap<Nibbles, ProofNode> = BTreeMap::new();
+    for nodes in proof.into_values() {
+        for node in nodes {
+            by_path.entry(node.path.clone()).or_insert(node);
+        }
+    }
+    by_path.into_values().collect()
+}
+
+/// A compact proof reconstructable by a stateless verifier: the account-trie
+/// nodes and, per hashed address, the storage-trie nodes, each de-duplicated
+/// and sorted by path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StorageProof {
+    /// Account-trie proof nodes in ascending path order.
+    pub account: Vec<ProofNode>,
+    /// Storage-trie proof nodes per hashed address, each in ascending order.
+    pub storages: BTreeMap<B256, Vec<ProofNode>>,
+}
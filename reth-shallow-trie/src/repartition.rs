@@ -0,0 +1,173 @@
+// =============================================================================
+// Runtime-configurable split depth with online re-partitioning.
+//
+// The shallow/deep cutoff is a policy, not a compile-time constant: operators
+// may tune it to match observed trie shape (e.g. deeper shallow tables for
+// dense top levels). [`ShallowDepthPolicy`] carries the active threshold in
+// memory (nothing here persists it), and `repartition_*` migrates rows
+// between the shallow and deep tables in a single transaction when the boundary
+// changes, without a full reindex.
+// =============================================================================
+
+use alloy_primitives::B256;
+
+use reth_db_api::{
+    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW},
+    tables,
+    transaction::{DbTx, DbTxMut},
+    DatabaseError,
+};
+use reth_trie::{
+    BranchNodeCompact, StorageTrieEntry, StoredNibbles, StoredNibblesSubKey,
+};
+use reth_trie_common::constants::SHALLOW_TRIE_DEPTH;
+
+/// The active shallow/deep boundary. Defaults to [`SHALLOW_TRIE_DEPTH`]; a node
+/// whose path length is `<= depth` belongs in the shallow table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShallowDepthPolicy {
+    depth: usize,
+}
+
+impl Default for ShallowDepthPolicy {
+    fn default() -> Self {
+        Self { depth: SHALLOW_TRIE_DEPTH }
+    }
+}
+
+impl ShallowDepthPolicy {
+    /// Create a policy with an explicit depth.
+    pub const fn new(depth: usize) -> Self {
+        Self { depth }
+    }
+
+    /// The active threshold.
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Whether a path of `len` nibbles belongs in the shallow table.
+    pub const fn is_shallow(&self, len: usize) -> bool {
+        len <= self.depth
+    }
+}
+
+/// Migrate account trie rows so the shallow/deep split reflects `new_depth`.
+///
+/// Returns the number of rows moved. Rows are collected before any mutation so
+/// the walking cursor is never invalidated mid-scan; the tables stay sorted by
+/// key regardless of insertion order.
+pub fn repartition_account_tables<TX: DbTxMut>(
+    tx: &TX,
+    old_depth: usize,
+    new_depth: usize,
+) -> Result<usize, DatabaseError> {
+    if new_depth == old_depth {
+        return Ok(0);
+    }
+
+    // Lengths that change tier: (min_exclusive, max_inclusive].
+    let (lo, hi) = (old_depth.min(new_depth), old_depth.max(new_depth));
+    let deepening = new_depth > old_depth;
+
+    // Collect the rows that cross the boundary from the source table, before
+    // mutating, so the walking cursor is never invalidated mid-scan.
+    let mut moved: Vec<(StoredNibbles, BranchNodeCompact)> = Vec::new();
+    if deepening {
+        // Moving deep -> shallow: read from the deep table.
+        let mut c = tx.cursor_read::<tables::AccountsTrie>()?;
+        let mut walker = c.walk(None)?;
+        while let Some((k, v)) = walker.next().transpose()? {
+            if k.0.len() > lo && k.0.len() <= hi {
+                moved.push((k, v));
+            }
+        }
+    } else {
+        // Moving shallow -> deep: read from the shallow table.
+        let mut c = tx.cursor_read::<tables::AccountsTrieShallow>()?;
+        let mut walker = c.walk(None)?;
+        while let Some((k, v)) = walker.next().transpose()? {
+            if k.0.len() > lo && k.0.len() <= hi {
+                moved.push((k, v));
+            }
+        }
+    }
+
+    let mut shallow = tx.cursor_write::<tables::AccountsTrieShallow>()?;
+    let mut deep = tx.cursor_write::<tables::AccountsTrie>()?;
+    for (key, node) in &moved {
+        if deepening {
+            if deep.seek_exact(key.clone())?.is_some() {
+                deep.delete_current()?;
+            }
+            shallow.upsert(key.clone(), node)?;
+        } else {
+            if shallow.seek_exact(key.clone())?.is_some() {
+                shallow.delete_current()?;
+            }
+            deep.upsert(key.clone(), node)?;
+        }
+    }
+
+    Ok(moved.len())
+}
+
+/// Migrate storage trie rows so the shallow/deep split reflects `new_depth`.
+pub fn repartition_storage_tables<TX: DbTxMut>(
+    tx: &TX,
+    old_depth: usize,
+    new_depth: usize,
+) -> Result<usize, DatabaseError> {
+    if new_depth == old_depth {
+        return Ok(0);
+    }
+
+    let (lo, hi) = (old_depth.min(new_depth), old_depth.max(new_depth));
+    let deepening = new_depth > old_depth;
+
+    let mut moved: Vec<(B256, StorageTrieEntry)> = Vec::new();
+    if deepening {
+        let mut c = tx.cursor_dup_read::<tables::StoragesTrie>()?;
+        let mut walker = c.walk(None)?;
+        while let Some((addr, entry)) = walker.next().transpose()? {
+            if entry.nibbles.0.len() > lo && entry.nibbles.0.len() <= hi {
+                moved.push((addr, entry));
+            }
+        }
+    } else {
+        let mut c = tx.cursor_dup_read::<tables::StoragesTrieShallow>()?;
+        let mut walker = c.walk(None)?;
+        while let Some((addr, entry)) = walker.next().transpose()? {
+            if entry.nibbles.0.len() > lo && entry.nibbles.0.len() <= hi {
+                moved.push((addr, entry));
+            }
+        }
+    }
+
+    let mut shallow = tx.cursor_dup_write::<tables::StoragesTrieShallow>()?;
+    let mut deep = tx.cursor_dup_write::<tables::StoragesTrie>()?;
+    for (addr, entry) in &moved {
+        let subkey: StoredNibblesSubKey = entry.nibbles.clone();
+        if deepening {
+            if deep
+                .seek_by_key_subkey(*addr, subkey.clone())?
+                .filter(|e| e.nibbles == subkey)
+                .is_some()
+            {
+                deep.delete_current()?;
+            }
+            shallow.upsert(*addr, entry)?;
+        } else {
+            if shallow
+                .seek_by_key_subkey(*addr, subkey.clone())?
+                .filter(|e| e.nibbles == subkey)
+                .is_some()
+            {
+                shallow.delete_current()?;
+            }
+            deep.upsert(*addr, entry)?;
+        }
+    }
+
+    Ok(moved.len())
+}
@@ -0,0 +1,225 @@
+// =============================================================================
+// In-memory resident cache for the shallow trie tables ("memtrie").
+//
+// `SHALLOW_TRIE_DEPTH` nodes are ~648 MB (0.5% of the trie) yet touched on
+// nearly every state read. This module loads the whole `AccountsTrieShallow`
+// and `StoragesTrieShallow` contents into RAM on open and serves shallow reads
+// from there, leaving the deep tables on disk.
+//
+// Target file (reth): `crates/trie/db/src/trie_cursor.rs`, alongside
+// `DatabaseTrieCursorFactory`.
+// =============================================================================
+
+use alloy_primitives::B256;
+use std::collections::BTreeMap;
+
+use reth_db_api::{
+    cursor::{DbCursorRO, DbDupCursorRO},
+    tables,
+    transaction::DbTx,
+    DatabaseError,
+};
+use reth_trie::{
+    trie_cursor::TrieCursor, BranchNodeCompact, Nibbles, StoredNibbles, StoredNibblesSubKey,
+};
+
+/// Resident in-memory copy of the shallow trie tables.
+///
+/// Accounts and per-address storage are held in `BTreeMap`s so that the
+/// [`TrieCursor`] implementations below get the ascending `seek`/`next`
+/// semantics cursors require without re-sorting on every open; a plain
+/// `HashMap` would force a sort per cursor.
+#[derive(Debug, Default, Clone)]
+pub struct ShallowTrieMem {
+    /// Shallow account nodes, keyed by path.
+    accounts: BTreeMap<StoredNibbles, BranchNodeCompact>,
+    /// Shallow storage nodes, grouped by hashed address then path.
+    storages: BTreeMap<B256, BTreeMap<StoredNibblesSubKey, BranchNodeCompact>>,
+}
+
+impl ShallowTrieMem {
+    /// Build an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a cache directly from pre-sorted shallow maps (e.g. a stateless
+    /// witness of the top-of-trie nodes) without touching the database.
+    pub fn from_maps(
+        accounts: BTreeMap<StoredNibbles, BranchNodeCompact>,
+        storages: BTreeMap<B256, BTreeMap<StoredNibblesSubKey, BranchNodeCompact>>,
+    ) -> Self {
+        Self { accounts, storages }
+    }
+
+    /// Populate the cache with a full scan of both shallow tables from a
+    /// consistent transaction snapshot.
+    pub fn load<TX: DbTx>(tx: &TX) -> Result<Self, DatabaseError> {
+        let mut this = Self::new();
+
+        let mut account_cursor = tx.cursor_read::<tables::AccountsTrieShallow>()?;
+        let mut walker = account_cursor.walk(None)?;
+        while let Some((key, node)) = walker.next().transpose()? {
+            this.accounts.insert(key, node);
+        }
+
+        let mut storage_cursor = tx.cursor_dup_read::<tables::StoragesTrieShallow>()?;
+        let mut walker = storage_cursor.walk(None)?;
+        while let Some((address, entry)) = walker.next().transpose()? {
+            this.storages.entry(address).or_default().insert(entry.nibbles, entry.node);
+        }
+
+        Ok(this)
+    }
+
+    /// Upsert or remove a shallow account node, keeping the cache consistent
+    /// with a DB write. `None` removes the key.
+    pub fn set_account(&mut self, nibbles: &Nibbles, node: Option<&BranchNodeCompact>) {
+        let key = StoredNibbles(nibbles.clone());
+        match node {
+            Some(node) => {
+                self.accounts.insert(key, node.clone());
+            }
+            None => {
+                self.accounts.remove(&key);
+            }
+        }
+    }
+
+    /// Upsert or remove a shallow storage node for `hashed_address`.
+    pub fn set_storage(
+        &mut self,
+        hashed_address: B256,
+        nibbles: &Nibbles,
+        node: Option<&BranchNodeCompact>,
+    ) {
+        let key = StoredNibblesSubKey(*nibbles);
+        match node {
+            Some(node) => {
+                self.storages.entry(hashed_address).or_default().insert(key, node.clone());
+            }
+            None => {
+                if let Some(map) = self.storages.get_mut(&hashed_address) {
+                    map.remove(&key);
+                    if map.is_empty() {
+                        self.storages.remove(&hashed_address);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drop all shallow storage nodes for an account (the `is_deleted()` case).
+    pub fn clear_storage(&mut self, hashed_address: B256) {
+        self.storages.remove(&hashed_address);
+    }
+
+    /// Cursor over the resident shallow account nodes.
+    pub fn account_cursor(&self) -> ShallowMemAccountCursor<'_> {
+        ShallowMemAccountCursor { map: &self.accounts, pos: None }
+    }
+
+    /// Cursor over the resident shallow storage nodes of one account.
+    pub fn storage_cursor(&self, hashed_address: B256) -> ShallowMemStorageCursor<'_> {
+        ShallowMemStorageCursor { map: self.storages.get(&hashed_address), pos: None }
+    }
+}
+
+/// Cursor over the in-memory shallow account map.
+#[derive(Debug)]
+pub struct ShallowMemAccountCursor<'a> {
+    map: &'a BTreeMap<StoredNibbles, BranchNodeCompact>,
+    pos: Option<StoredNibbles>,
+}
+
+impl TrieCursor for ShallowMemAccountCursor<'_> {
+    fn seek_exact(
+        &mut self,
+        key: Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        let key = StoredNibbles(key);
+        Ok(self.map.get_key_value(&key).map(|(k, v)| {
+            self.pos = Some(k.clone());
+            (k.0.clone(), v.clone())
+        }))
+    }
+
+    fn seek(
+        &mut self,
+        key: Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        let key = StoredNibbles(key);
+        Ok(self.map.range(key..).next().map(|(k, v)| {
+            self.pos = Some(k.clone());
+            (k.0.clone(), v.clone())
+        }))
+    }
+
+    fn next(&mut self) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        let Some(current) = self.pos.clone() else { return Ok(None) };
+        use std::ops::Bound::{Excluded, Unbounded};
+        Ok(self.map.range((Excluded(current), Unbounded)).next().map(|(k, v)| {
+            self.pos = Some(k.clone());
+            (k.0.clone(), v.clone())
+        }))
+    }
+
+    fn current(&mut self) -> Result<Option<Nibbles>, DatabaseError> {
+        Ok(self.pos.as_ref().map(|k| k.0.clone()))
+    }
+
+    fn reset(&mut self) {
+        self.pos = None;
+    }
+}
+
+/// Cursor over the in-memory shallow storage map of a single account.
+#[derive(Debug)]
+pub struct ShallowMemStorageCursor<'a> {
+    map: Option<&'a BTreeMap<StoredNibblesSubKey, BranchNodeCompact>>,
+    pos: Option<StoredNibblesSubKey>,
+}
+
+impl TrieCursor for ShallowMemStorageCursor<'_> {
+    fn seek_exact(
+        &mut self,
+        key: Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        let Some(map) = self.map else { return Ok(None) };
+        let key = StoredNibblesSubKey(key);
+        Ok(map.get_key_value(&key).map(|(k, v)| {
+            self.pos = Some(k.clone());
+            (k.0, v.clone())
+        }))
+    }
+
+    fn seek(
+        &mut self,
+        key: Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        let Some(map) = self.map else { return Ok(None) };
+        let key = StoredNibblesSubKey(key);
+        Ok(map.range(key..).next().map(|(k, v)| {
+            self.pos = Some(k.clone());
+            (k.0, v.clone())
+        }))
+    }
+
+    fn next(&mut self) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        let Some(map) = self.map else { return Ok(None) };
+        let Some(current) = self.pos.clone() else { return Ok(None) };
+        use std::ops::Bound::{Excluded, Unbounded};
+        Ok(map.range((Excluded(current), Unbounded)).next().map(|(k, v)| {
+            self.pos = Some(k.clone());
+            (k.0, v.clone())
+        }))
+    }
+
+    fn current(&mut self) -> Result<Option<Nibbles>, DatabaseError> {
+        Ok(self.pos.as_ref().map(|k| k.0))
+    }
+
+    fn reset(&mut self) {
+        self.pos = None;
+    }
+}
@@ -33,3 +33,26 @@
         type Value = StorageTrieEntry;
         type SubKey = StoredNibblesSubKey;
     }
+
+// --- Trie history tables (changeset-style, keyed by block) ---
+//
+// Borrowing the commit-number model from muxdb's `CleanTrieHistory`, these
+// tables retain the pre-image a trie node held *before* it was overwritten at
+// a given block, so old revisions can be kept for a configurable window and
+// then bulk-pruned by block range (see `prune_trie_history`). Keying by
+// `BlockNumber` with the path as dup subkey makes range pruning a contiguous
+// key-range delete.
+
+    /// Pre-images of account trie nodes overwritten at a block.
+    table AccountsTrieHistory {
+        type Key = BlockNumber;
+        type Value = AccountTrieHistoryEntry;
+        type SubKey = StoredNibbles;
+    }
+
+    /// Pre-images of storage trie nodes overwritten at a block.
+    table StoragesTrieHistory {
+        type Key = BlockNumber;
+        type Value = StorageTrieHistoryEntry;
+        type SubKey = StoredNibblesSubKey;
+    }
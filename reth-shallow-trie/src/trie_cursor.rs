@@ -7,7 +7,7 @@ use reth_db_api::{
 };
 use reth_trie::{
     trie_cursor::{TrieCursor, TrieCursorFactory, TrieStorageCursor},
-    updates::StorageTrieUpdatesSorted,
+    updates::{StorageTrieUpdatesSorted, TrieUpdatesSorted},
     BranchNodeCompact, Nibbles, StorageTrieEntry, StoredNibbles, StoredNibblesSubKey,
 };
 use reth_trie_common::constants::SHALLOW_TRIE_DEPTH;
@@ -18,12 +18,30 @@ use reth_trie_common::constants::SHALLOW_TRIE_DEPTH;
 
 /// Wrapper struct for database transaction implementing trie cursor factory trait.
 #[derive(Debug, Clone)]
-pub struct DatabaseTrieCursorFactory<T>(T);
+pub struct DatabaseTrieCursorFactory<T> {
+    tx: T,
+    /// Active shallow/deep boundary, handed to every cursor so reads and writes
+    /// route consistently with whatever [`crate::repartition`] last migrated.
+    shallow_depth: usize,
+}
 
 impl<T> DatabaseTrieCursorFactory<T> {
-    /// Create new [`DatabaseTrieCursorFactory`].
+    /// Create new [`DatabaseTrieCursorFactory`] with the default shallow depth.
     pub const fn new(tx: T) -> Self {
-        Self(tx)
+        Self { tx, shallow_depth: SHALLOW_TRIE_DEPTH }
+    }
+
+    /// Create a factory with an explicit shallow/deep boundary (e.g. after a
+    /// [`crate::repartition`] to a non-default depth).
+    pub const fn new_with_depth(tx: T, shallow_depth: usize) -> Self {
+        Self { tx, shallow_depth }
+    }
+
+    /// Create a factory whose cursors route by the given
+    /// [`ShallowDepthPolicy`](crate::repartition::ShallowDepthPolicy), so reads
+    /// match whatever boundary `repartition_*` last migrated to.
+    pub const fn with_policy(tx: T, policy: crate::repartition::ShallowDepthPolicy) -> Self {
+        Self { tx, shallow_depth: policy.depth() }
     }
 }
 
@@ -48,21 +66,48 @@ where
         Self: 'a;
 
     fn account_trie_cursor(&self) -> Result<Self::AccountTrieCursor<'_>, DatabaseError> {
-        let shallow = self.0.cursor_read::<tables::AccountsTrieShallow>()?;
-        let deep = self.0.cursor_read::<tables::AccountsTrie>()?;
-        Ok(SplitAccountTrieCursor::new(shallow, deep))
+        let shallow = self.tx.cursor_read::<tables::AccountsTrieShallow>()?;
+        let deep = self.tx.cursor_read::<tables::AccountsTrie>()?;
+        Ok(SplitAccountTrieCursor::new_with_depth(shallow, deep, self.shallow_depth))
     }
 
     fn storage_trie_cursor(
         &self,
         hashed_address: B256,
     ) -> Result<Self::StorageTrieCursor<'_>, DatabaseError> {
-        let shallow = self.0.cursor_dup_read::<tables::StoragesTrieShallow>()?;
-        let deep = self.0.cursor_dup_read::<tables::StoragesTrie>()?;
-        Ok(SplitStorageTrieCursor::new(shallow, deep, hashed_address))
+        let shallow = self.tx.cursor_dup_read::<tables::StoragesTrieShallow>()?;
+        let deep = self.tx.cursor_dup_read::<tables::StoragesTrie>()?;
+        Ok(SplitStorageTrieCursor::new_with_depth(shallow, deep, hashed_address, self.shallow_depth))
     }
 }
 
+/// Public alias for [`DatabaseTrieCursorFactory`] spelled the way the rest of
+/// the split-table stack refers to it: the factory that opens one cursor over
+/// the shallow table and one over the deep table and merges them on reads.
+///
+/// Naming alias only — the merge/dup-positioning behavior already lived in the
+/// baseline [`SplitAccountTrieCursor`]/[`SplitStorageTrieCursor`]; this exposes
+/// it under the name the split-table stack expects, it is not a reimplementation.
+pub type SplitDatabaseTrieCursorFactory<T> = DatabaseTrieCursorFactory<T>;
+
+/// Public alias for the account-side split cursor returned by
+/// [`SplitDatabaseTrieCursorFactory::account_trie_cursor`].
+pub type SplitDatabaseAccountTrieCursor<CS, CD> = SplitAccountTrieCursor<CS, CD>;
+
+/// Public alias for the storage-side split cursor returned by
+/// [`SplitDatabaseTrieCursorFactory::storage_trie_cursor`].
+pub type SplitDatabaseStorageTrieCursor<CS, CD> = SplitStorageTrieCursor<CS, CD>;
+
+/// Depth-aware account trie cursor that hides the shallow/deep split from
+/// callers, routing point lookups by path length and k-way merging range
+/// scans. Naming alias for the baseline [`SplitAccountTrieCursor`] — the
+/// routing/merge behavior pre-exists; this only gives it the expected name.
+pub type ShallowSplitTrieCursor<CS, CD> = SplitAccountTrieCursor<CS, CD>;
+
+/// Storage analogue of [`ShallowSplitTrieCursor`], handling the dup-sorted
+/// `SubKey = StoredNibblesSubKey` case. Alias for [`SplitStorageTrieCursor`].
+pub type ShallowSplitStorageTrieCursor<CS, CD> = SplitStorageTrieCursor<CS, CD>;
+
 // =============================================================================
 // CursorSource — tracks which cursor was last consumed
 // =============================================================================
@@ -119,6 +164,27 @@ where
     }
 }
 
+impl<C> DatabaseAccountTrieCursor<C>
+where
+    C: DbCursorRO<tables::AccountsTrie> + Send,
+{
+    /// Greatest key `<= key` in the deep table.
+    fn seek_prev(
+        &mut self,
+        key: Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        match self.0.seek(StoredNibbles(key.clone()))? {
+            Some(v) if v.0 .0 == key => Ok(Some((v.0 .0, v.1))),
+            Some(_) => Ok(self.0.prev()?.map(|v| (v.0 .0, v.1))),
+            None => Ok(self.0.last()?.map(|v| (v.0 .0, v.1))),
+        }
+    }
+
+    fn prev_node(&mut self) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        Ok(self.0.prev()?.map(|v| (v.0 .0, v.1)))
+    }
+}
+
 // =============================================================================
 // ShallowAccountTrieCursor — typed for AccountsTrieShallow table
 // =============================================================================
@@ -152,6 +218,22 @@ where
     fn current(&mut self) -> Result<Option<Nibbles>, DatabaseError> {
         Ok(self.0.current()?.map(|(k, _)| k.0))
     }
+
+    /// Greatest key `<= key` in the shallow table.
+    fn seek_prev(
+        &mut self,
+        key: Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        match self.0.seek(StoredNibbles(key.clone()))? {
+            Some(v) if v.0 .0 == key => Ok(Some((v.0 .0, v.1))),
+            Some(_) => Ok(self.0.prev()?.map(|v| (v.0 .0, v.1))),
+            None => Ok(self.0.last()?.map(|v| (v.0 .0, v.1))),
+        }
+    }
+
+    fn prev(&mut self) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        Ok(self.0.prev()?.map(|v| (v.0 .0, v.1)))
+    }
 }
 
 // =============================================================================
@@ -175,17 +257,27 @@ pub struct SplitAccountTrieCursor<CS, CD> {
     pending_deep: Option<(Nibbles, BranchNodeCompact)>,
     /// Which cursor was consumed on the last call (used by `current()`).
     last_consumed: Option<CursorSource>,
+    /// Active shallow/deep boundary: paths with `len() <= shallow_depth` live in
+    /// the shallow table. Kept in sync with the factory so point lookups and
+    /// writes route the same way after a [`crate::repartition`].
+    shallow_depth: usize,
 }
 
 impl<CS, CD> SplitAccountTrieCursor<CS, CD> {
-    /// Create a new split account trie cursor.
+    /// Create a new split account trie cursor with the default shallow depth.
     pub fn new(shallow: CS, deep: CD) -> Self {
+        Self::new_with_depth(shallow, deep, SHALLOW_TRIE_DEPTH)
+    }
+
+    /// Create a new split account trie cursor with an explicit shallow depth.
+    pub fn new_with_depth(shallow: CS, deep: CD, shallow_depth: usize) -> Self {
         Self {
             shallow: ShallowAccountTrieCursor(shallow),
             deep: DatabaseAccountTrieCursor::new(deep),
             pending_shallow: None,
             pending_deep: None,
             last_consumed: None,
+            shallow_depth,
         }
     }
 }
@@ -222,6 +314,105 @@ where
             }
         }
     }
+
+    /// Reverse of [`Self::consume_smaller`]: return the larger buffered entry,
+    /// leaving the other buffered. Used by descending traversal.
+    fn consume_larger(&mut self) -> Option<(Nibbles, BranchNodeCompact)> {
+        match (&self.pending_shallow, &self.pending_deep) {
+            (Some((s, _)), Some((d, _))) => {
+                if s >= d {
+                    self.last_consumed = Some(CursorSource::Shallow);
+                    self.pending_shallow.take()
+                } else {
+                    self.last_consumed = Some(CursorSource::Deep);
+                    self.pending_deep.take()
+                }
+            }
+            (Some(_), None) => {
+                self.last_consumed = Some(CursorSource::Shallow);
+                self.pending_shallow.take()
+            }
+            (None, Some(_)) => {
+                self.last_consumed = Some(CursorSource::Deep);
+                self.pending_deep.take()
+            }
+            (None, None) => {
+                self.last_consumed = None;
+                None
+            }
+        }
+    }
+
+    /// Position the cursor at the greatest key `<= key` across both tables and
+    /// return it, beginning a descending traversal.
+    pub fn seek_prev(
+        &mut self,
+        key: Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        self.pending_shallow = self.shallow.seek_prev(key.clone())?;
+        self.pending_deep = self.deep.seek_prev(key)?;
+        Ok(self.consume_larger())
+    }
+
+    /// Return the greatest key strictly less than the current position,
+    /// interleaving the two tables in descending order.
+    pub fn prev(&mut self) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        match self.last_consumed {
+            Some(CursorSource::Shallow) => self.pending_shallow = self.shallow.prev()?,
+            Some(CursorSource::Deep) => self.pending_deep = self.deep.prev_node()?,
+            None => {
+                if self.pending_shallow.is_none() {
+                    self.pending_shallow = self.shallow.prev()?;
+                }
+                if self.pending_deep.is_none() {
+                    self.pending_deep = self.deep.prev_node()?;
+                }
+            }
+        }
+        Ok(self.consume_larger())
+    }
+}
+
+impl<CS, CD> SplitAccountTrieCursor<CS, CD>
+where
+    CS: DbCursorRO<tables::AccountsTrieShallow>
+        + DbCursorRW<tables::AccountsTrieShallow>
+        + Send,
+    CD: DbCursorRO<tables::AccountsTrie> + DbCursorRW<tables::AccountsTrie> + Send,
+{
+    /// Write sorted account trie updates, routing each node to the shallow or
+    /// deep table by `nibbles.len() <= self.shallow_depth` — the write-side
+    /// mirror of this cursor's read routing, so a node is never written to a
+    /// table the reads won't consult.
+    pub fn write_account_trie_updates_sorted(
+        &mut self,
+        updates: &TrieUpdatesSorted,
+    ) -> Result<usize, DatabaseError> {
+        let mut num_entries = 0;
+        for (nibbles, maybe_node) in &updates.account_nodes {
+            if nibbles.is_empty() {
+                continue;
+            }
+            num_entries += 1;
+            let stored = StoredNibbles(nibbles.clone());
+            if nibbles.len() <= self.shallow_depth {
+                if self.shallow.0.seek_exact(stored.clone())?.is_some() {
+                    self.shallow.0.delete_current()?;
+                }
+                if let Some(node) = maybe_node {
+                    self.shallow.0.upsert(stored, node)?;
+                }
+            } else {
+                if self.deep.0.seek_exact(stored.clone())?.is_some() {
+                    self.deep.0.delete_current()?;
+                }
+                if let Some(node) = maybe_node {
+                    self.deep.0.upsert(stored, node)?;
+                }
+            }
+        }
+        Ok(num_entries)
+    }
 }
 
 impl<CS, CD> TrieCursor for SplitAccountTrieCursor<CS, CD>
@@ -237,7 +428,7 @@ where
         self.pending_shallow = None;
         self.pending_deep = None;
 
-        if key.len() <= SHALLOW_TRIE_DEPTH {
+        if key.len() <= self.shallow_depth {
             self.last_consumed = Some(CursorSource::Shallow);
             self.shallow.seek_exact(key)
         } else {
@@ -400,6 +591,38 @@ where
     }
 }
 
+impl<C> DatabaseStorageTrieCursor<C>
+where
+    C: DbCursorRO<tables::StoragesTrie> + DbDupCursorRO<tables::StoragesTrie> + Send,
+{
+    /// Greatest subkey `<= key` for the current account in the deep table.
+    fn seek_prev(
+        &mut self,
+        key: Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        let subkey = StoredNibblesSubKey(key);
+        match self.cursor.seek_by_key_subkey(self.hashed_address, subkey.clone())? {
+            Some(e) if e.nibbles == subkey => Ok(Some((e.nibbles.0, e.node))),
+            Some(_) => Ok(self.cursor.prev_dup()?.map(|(_, v)| (v.nibbles.0, v.node))),
+            None => {
+                if self
+                    .cursor
+                    .seek_by_key_subkey(self.hashed_address, StoredNibblesSubKey(Nibbles::default()))?
+                    .is_none()
+                {
+                    return Ok(None);
+                }
+                while self.cursor.next_dup()?.is_some() {}
+                Ok(self.cursor.current()?.map(|(_, v)| (v.nibbles.0, v.node)))
+            }
+        }
+    }
+
+    fn prev_node(&mut self) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        Ok(self.cursor.prev_dup()?.map(|(_, v)| (v.nibbles.0, v.node)))
+    }
+}
+
 // =============================================================================
 // ShallowStorageTrieCursor — typed for StoragesTrieShallow
 // =============================================================================
@@ -448,6 +671,35 @@ where
     fn set_hashed_address(&mut self, hashed_address: B256) {
         self.hashed_address = hashed_address;
     }
+
+    /// Greatest subkey `<= key` for the current account.
+    fn seek_prev(
+        &mut self,
+        key: Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        let subkey = StoredNibblesSubKey(key);
+        match self.cursor.seek_by_key_subkey(self.hashed_address, subkey.clone())? {
+            Some(e) if e.nibbles == subkey => Ok(Some((e.nibbles.0, e.node))),
+            Some(_) => Ok(self.cursor.prev_dup()?.map(|(_, v)| (v.nibbles.0, v.node))),
+            None => {
+                // No subkey >= key: walk to the final duplicate, which is the
+                // greatest subkey for this account.
+                if self
+                    .cursor
+                    .seek_by_key_subkey(self.hashed_address, StoredNibblesSubKey(Nibbles::default()))?
+                    .is_none()
+                {
+                    return Ok(None);
+                }
+                while self.cursor.next_dup()?.is_some() {}
+                Ok(self.cursor.current()?.map(|(_, v)| (v.nibbles.0, v.node)))
+            }
+        }
+    }
+
+    fn prev(&mut self) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        Ok(self.cursor.prev_dup()?.map(|(_, v)| (v.nibbles.0, v.node)))
+    }
 }
 
 // =============================================================================
@@ -464,16 +716,30 @@ pub struct SplitStorageTrieCursor<CS, CD> {
     pending_shallow: Option<(Nibbles, BranchNodeCompact)>,
     pending_deep: Option<(Nibbles, BranchNodeCompact)>,
     last_consumed: Option<CursorSource>,
+    /// Active shallow/deep boundary; see [`SplitAccountTrieCursor`].
+    shallow_depth: usize,
 }
 
 impl<CS, CD> SplitStorageTrieCursor<CS, CD> {
+    /// Create a new split storage trie cursor with the default shallow depth.
     pub fn new(shallow: CS, deep: CD, hashed_address: B256) -> Self {
+        Self::new_with_depth(shallow, deep, hashed_address, SHALLOW_TRIE_DEPTH)
+    }
+
+    /// Create a new split storage trie cursor with an explicit shallow depth.
+    pub fn new_with_depth(
+        shallow: CS,
+        deep: CD,
+        hashed_address: B256,
+        shallow_depth: usize,
+    ) -> Self {
         Self {
             shallow: ShallowStorageTrieCursor { cursor: shallow, hashed_address },
             deep: DatabaseStorageTrieCursor::new(deep, hashed_address),
             pending_shallow: None,
             pending_deep: None,
             last_consumed: None,
+            shallow_depth,
         }
     }
 }
@@ -512,6 +778,137 @@ where
             }
         }
     }
+
+    /// Reverse of [`Self::consume_smaller`]: return the larger buffered entry.
+    fn consume_larger(&mut self) -> Option<(Nibbles, BranchNodeCompact)> {
+        match (&self.pending_shallow, &self.pending_deep) {
+            (Some((s, _)), Some((d, _))) => {
+                if s >= d {
+                    self.last_consumed = Some(CursorSource::Shallow);
+                    self.pending_shallow.take()
+                } else {
+                    self.last_consumed = Some(CursorSource::Deep);
+                    self.pending_deep.take()
+                }
+            }
+            (Some(_), None) => {
+                self.last_consumed = Some(CursorSource::Shallow);
+                self.pending_shallow.take()
+            }
+            (None, Some(_)) => {
+                self.last_consumed = Some(CursorSource::Deep);
+                self.pending_deep.take()
+            }
+            (None, None) => {
+                self.last_consumed = None;
+                None
+            }
+        }
+    }
+
+    /// Position at the greatest subkey `<= key` across both tables for the
+    /// current account and return it, starting a descending traversal.
+    pub fn seek_prev(
+        &mut self,
+        key: Nibbles,
+    ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        self.pending_shallow = self.shallow.seek_prev(key.clone())?;
+        self.pending_deep = self.deep.seek_prev(key)?;
+        Ok(self.consume_larger())
+    }
+
+    /// Return the greatest subkey strictly less than the current position.
+    pub fn prev(&mut self) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+        match self.last_consumed {
+            Some(CursorSource::Shallow) => self.pending_shallow = self.shallow.prev()?,
+            Some(CursorSource::Deep) => self.pending_deep = self.deep.prev_node()?,
+            None => {
+                if self.pending_shallow.is_none() {
+                    self.pending_shallow = self.shallow.prev()?;
+                }
+                if self.pending_deep.is_none() {
+                    self.pending_deep = self.deep.prev_node()?;
+                }
+            }
+        }
+        Ok(self.consume_larger())
+    }
+}
+
+impl<CS, CD> SplitStorageTrieCursor<CS, CD>
+where
+    CS: DbCursorRO<tables::StoragesTrieShallow>
+        + DbCursorRW<tables::StoragesTrieShallow>
+        + DbDupCursorRO<tables::StoragesTrieShallow>
+        + DbDupCursorRW<tables::StoragesTrieShallow>
+        + Send,
+    CD: DbCursorRO<tables::StoragesTrie>
+        + DbCursorRW<tables::StoragesTrie>
+        + DbDupCursorRO<tables::StoragesTrie>
+        + DbDupCursorRW<tables::StoragesTrie>
+        + Send,
+{
+    /// Split-aware replacement for
+    /// [`DatabaseStorageTrieCursor::write_storage_trie_updates_sorted`]: routes
+    /// each node to the shallow or deep dup-sorted table by subkey length, and
+    /// on `is_deleted()` clears the account's duplicates in *both* tables.
+    pub fn write_storage_trie_updates_sorted(
+        &mut self,
+        updates: &StorageTrieUpdatesSorted,
+    ) -> Result<usize, DatabaseError> {
+        let hashed_address = self.deep.hashed_address;
+
+        if updates.is_deleted() {
+            if self.shallow.cursor.seek_exact(hashed_address)?.is_some() {
+                self.shallow.cursor.delete_current_duplicates()?;
+            }
+            if self.deep.cursor.seek_exact(hashed_address)?.is_some() {
+                self.deep.cursor.delete_current_duplicates()?;
+            }
+        }
+
+        let mut num_entries = 0;
+        for (nibbles, maybe_node) in
+            updates.storage_nodes.iter().filter(|(n, _)| !n.is_empty())
+        {
+            num_entries += 1;
+            let subkey = StoredNibblesSubKey(*nibbles);
+            if nibbles.len() <= self.shallow_depth {
+                if self
+                    .shallow
+                    .cursor
+                    .seek_by_key_subkey(hashed_address, subkey.clone())?
+                    .filter(|e| e.nibbles == subkey)
+                    .is_some()
+                {
+                    self.shallow.cursor.delete_current()?;
+                }
+                if let Some(node) = maybe_node {
+                    self.shallow.cursor.upsert(
+                        hashed_address,
+                        &StorageTrieEntry { nibbles: subkey, node: node.clone() },
+                    )?;
+                }
+            } else {
+                if self
+                    .deep
+                    .cursor
+                    .seek_by_key_subkey(hashed_address, subkey.clone())?
+                    .filter(|e| e.nibbles == subkey)
+                    .is_some()
+                {
+                    self.deep.cursor.delete_current()?;
+                }
+                if let Some(node) = maybe_node {
+                    self.deep.cursor.upsert(
+                        hashed_address,
+                        &StorageTrieEntry { nibbles: subkey, node: node.clone() },
+                    )?;
+                }
+            }
+        }
+        Ok(num_entries)
+    }
 }
 
 impl<CS, CD> TrieCursor for SplitStorageTrieCursor<CS, CD>
@@ -530,7 +927,7 @@ where
         self.pending_shallow = None;
         self.pending_deep = None;
 
-        if key.len() <= SHALLOW_TRIE_DEPTH {
+        if key.len() <= self.shallow_depth {
             self.last_consumed = Some(CursorSource::Shallow);
             self.shallow.seek_exact(key)
         } else {
@@ -833,6 +1230,600 @@ mod tests {
         assert!(cursor.next().unwrap().is_none());
     }
 
+    #[test]
+    fn split_cursor_seek_exact_honors_runtime_depth() {
+        // After a repartition to depth 6, a length-6 node lives in the shallow
+        // table. A factory carrying that depth must route `seek_exact` there;
+        // the default-depth factory would wrongly look in the deep table.
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let node = test_node();
+
+        let path = Nibbles::from_nibbles([0x1, 0x2, 0x3, 0x4, 0x5, 0x6]);
+        provider
+            .tx_ref()
+            .cursor_write::<tables::AccountsTrieShallow>()
+            .unwrap()
+            .upsert(StoredNibbles(path.clone()), &node)
+            .unwrap();
+
+        // Default depth (5) routes the length-6 lookup to the empty deep table.
+        let default_factory = DatabaseTrieCursorFactory::new(provider.tx_ref());
+        let mut default_cursor = default_factory.account_trie_cursor().unwrap();
+        assert!(default_cursor.seek_exact(path.clone()).unwrap().is_none());
+
+        // Depth-6 factory routes it to the shallow table and finds the node.
+        let deep_factory = DatabaseTrieCursorFactory::new_with_depth(provider.tx_ref(), 6);
+        let mut deep_cursor = deep_factory.account_trie_cursor().unwrap();
+        assert_eq!(deep_cursor.seek_exact(path.clone()).unwrap().unwrap().0, path);
+    }
+
+    #[test]
+    fn repartition_then_seek_exact_finds_migrated_node() {
+        // End-to-end: a length-6 node starts in the deep table under depth 5,
+        // a repartition to depth 7 physically moves it into the shallow table,
+        // and a depth-7 factory's `seek_exact` must still find it. Before the
+        // runtime-depth fix this was a point-lookup miss on live data.
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let node = test_node();
+
+        let path = Nibbles::from_nibbles([0x1, 0x2, 0x3, 0x4, 0x5, 0x6]);
+        provider
+            .tx_ref()
+            .cursor_write::<tables::AccountsTrie>()
+            .unwrap()
+            .upsert(StoredNibbles(path.clone()), &node)
+            .unwrap();
+
+        let moved =
+            crate::repartition::repartition_account_tables(provider.tx_ref(), 5, 7).unwrap();
+        assert_eq!(moved, 1);
+
+        let f = DatabaseTrieCursorFactory::new_with_depth(provider.tx_ref(), 7);
+        let mut cursor = f.account_trie_cursor().unwrap();
+        assert_eq!(cursor.seek_exact(path.clone()).unwrap().unwrap().0, path);
+    }
+
+    // ---- Split storage cursor tests (mirror the account cursor tests) ----
+
+    #[test]
+    fn split_storage_cursor_seek_exact_routes_by_depth() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let node = test_node();
+        let hashed_address = B256::random();
+
+        // Shallow subkey (3 nibbles <= 5).
+        provider
+            .tx_ref()
+            .cursor_dup_write::<tables::StoragesTrieShallow>()
+            .unwrap()
+            .upsert(
+                hashed_address,
+                &StorageTrieEntry {
+                    nibbles: StoredNibblesSubKey(Nibbles::from_nibbles([0x1, 0x2, 0x3])),
+                    node: node.clone(),
+                },
+            )
+            .unwrap();
+
+        // Deep subkey (8 nibbles > 5).
+        provider
+            .tx_ref()
+            .cursor_dup_write::<tables::StoragesTrie>()
+            .unwrap()
+            .upsert(
+                hashed_address,
+                &StorageTrieEntry {
+                    nibbles: StoredNibblesSubKey(Nibbles::from_nibbles([
+                        0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8,
+                    ])),
+                    node: node.clone(),
+                },
+            )
+            .unwrap();
+
+        let f = DatabaseTrieCursorFactory::new(provider.tx_ref());
+        let mut cursor = f.storage_trie_cursor(hashed_address).unwrap();
+
+        assert!(cursor.seek_exact(Nibbles::from_nibbles([0x1, 0x2, 0x3])).unwrap().is_some());
+        assert!(cursor
+            .seek_exact(Nibbles::from_nibbles([0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8]))
+            .unwrap()
+            .is_some());
+        assert!(cursor.seek_exact(Nibbles::from_nibbles([0xf, 0xf])).unwrap().is_none());
+    }
+
+    #[test]
+    fn split_storage_cursor_seek_then_next_produces_sorted_merge() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let node = test_node();
+        let hashed_address = B256::random();
+
+        let mut sc =
+            provider.tx_ref().cursor_dup_write::<tables::StoragesTrieShallow>().unwrap();
+        for n in [[0x1], [0x3], [0x5]] {
+            sc.upsert(
+                hashed_address,
+                &StorageTrieEntry {
+                    nibbles: StoredNibblesSubKey(Nibbles::from_nibbles(n)),
+                    node: node.clone(),
+                },
+            )
+            .unwrap();
+        }
+        drop(sc);
+
+        let mut dc = provider.tx_ref().cursor_dup_write::<tables::StoragesTrie>().unwrap();
+        for n in [[0x2, 0x0, 0x0, 0x0, 0x0, 0x0], [0x4, 0x0, 0x0, 0x0, 0x0, 0x0]] {
+            dc.upsert(
+                hashed_address,
+                &StorageTrieEntry {
+                    nibbles: StoredNibblesSubKey(Nibbles::from_nibbles(n)),
+                    node: node.clone(),
+                },
+            )
+            .unwrap();
+        }
+        drop(dc);
+
+        let f = DatabaseTrieCursorFactory::new(provider.tx_ref());
+        let mut cursor = f.storage_trie_cursor(hashed_address).unwrap();
+
+        let first = cursor.seek(Nibbles::default()).unwrap();
+        let mut results = vec![first.unwrap().0];
+        while let Some((nibbles, _)) = cursor.next().unwrap() {
+            results.push(nibbles);
+        }
+
+        assert_eq!(results.len(), 5, "expected 5 entries, got {:?}", results);
+        for w in results.windows(2) {
+            assert!(w[0] < w[1], "not sorted: {:?} >= {:?}", w[0], w[1]);
+        }
+    }
+
+    // ---- Reverse traversal (prev / seek_prev) ----
+
+    #[test]
+    fn split_cursor_seek_prev_then_prev_descends_across_both_tables() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let node = test_node();
+
+        // Shallow keys (len <= 5) and deep keys (len > 5) interleaved by value.
+        let mut sc = provider.tx_ref().cursor_write::<tables::AccountsTrieShallow>().unwrap();
+        for n in [[0x1], [0x3], [0x5]] {
+            sc.upsert(StoredNibbles(Nibbles::from_nibbles(n)), &node).unwrap();
+        }
+        drop(sc);
+        let mut dc = provider.tx_ref().cursor_write::<tables::AccountsTrie>().unwrap();
+        for n in [[0x2, 0x0, 0x0, 0x0, 0x0, 0x0], [0x4, 0x0, 0x0, 0x0, 0x0, 0x0]] {
+            dc.upsert(StoredNibbles(Nibbles::from_nibbles(n)), &node).unwrap();
+        }
+        drop(dc);
+
+        let f = DatabaseTrieCursorFactory::new(provider.tx_ref());
+        let mut cursor = f.account_trie_cursor().unwrap();
+
+        // Start at the very end and walk backwards; the result must be the
+        // descending order of the ascending merge.
+        let last = cursor.seek_prev(Nibbles::from_nibbles([0xf])).unwrap();
+        let mut results = vec![last.unwrap().0];
+        while let Some((nibbles, _)) = cursor.prev().unwrap() {
+            results.push(nibbles);
+        }
+
+        assert_eq!(results.len(), 5, "expected 5 entries, got {:?}", results);
+        for w in results.windows(2) {
+            assert!(w[0] > w[1], "not descending: {:?} <= {:?}", w[0], w[1]);
+        }
+    }
+
+    #[test]
+    fn split_cursor_prev_only_shallow() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let node = test_node();
+
+        let mut sc = provider.tx_ref().cursor_write::<tables::AccountsTrieShallow>().unwrap();
+        sc.upsert(StoredNibbles(Nibbles::from_nibbles([0x1])), &node).unwrap();
+        sc.upsert(StoredNibbles(Nibbles::from_nibbles([0x2])), &node).unwrap();
+        drop(sc);
+
+        let f = DatabaseTrieCursorFactory::new(provider.tx_ref());
+        let mut cursor = f.account_trie_cursor().unwrap();
+
+        let last = cursor.seek_prev(Nibbles::from_nibbles([0xf])).unwrap();
+        assert_eq!(last.unwrap().0, Nibbles::from_nibbles([0x2]));
+        assert_eq!(cursor.prev().unwrap().unwrap().0, Nibbles::from_nibbles([0x1]));
+        assert!(cursor.prev().unwrap().is_none());
+    }
+
+    #[test]
+    fn split_cursor_prev_only_deep() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let node = test_node();
+
+        let mut dc = provider.tx_ref().cursor_write::<tables::AccountsTrie>().unwrap();
+        dc.upsert(StoredNibbles(Nibbles::from_nibbles([0x1, 0x0, 0x0, 0x0, 0x0, 0x0])), &node)
+            .unwrap();
+        dc.upsert(StoredNibbles(Nibbles::from_nibbles([0x2, 0x0, 0x0, 0x0, 0x0, 0x0])), &node)
+            .unwrap();
+        drop(dc);
+
+        let f = DatabaseTrieCursorFactory::new(provider.tx_ref());
+        let mut cursor = f.account_trie_cursor().unwrap();
+
+        let last = cursor.seek_prev(Nibbles::from_nibbles([0xf])).unwrap();
+        assert_eq!(last.unwrap().0, Nibbles::from_nibbles([0x2, 0x0, 0x0, 0x0, 0x0, 0x0]));
+        assert_eq!(
+            cursor.prev().unwrap().unwrap().0,
+            Nibbles::from_nibbles([0x1, 0x0, 0x0, 0x0, 0x0, 0x0])
+        );
+        assert!(cursor.prev().unwrap().is_none());
+    }
+
+    #[test]
+    fn split_cursor_seek_prev_empty_tables() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+
+        let f = DatabaseTrieCursorFactory::new(provider.tx_ref());
+        let mut cursor = f.account_trie_cursor().unwrap();
+
+        assert!(cursor.seek_prev(Nibbles::from_nibbles([0xf])).unwrap().is_none());
+        assert!(cursor.prev().unwrap().is_none());
+    }
+
+    #[test]
+    fn split_storage_cursor_seek_prev_then_prev_descends_across_both_tables() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let node = test_node();
+        let hashed_address = B256::random();
+
+        let mut sc = provider.tx_ref().cursor_dup_write::<tables::StoragesTrieShallow>().unwrap();
+        for n in [[0x1], [0x3], [0x5]] {
+            sc.upsert(
+                hashed_address,
+                &StorageTrieEntry {
+                    nibbles: StoredNibblesSubKey(Nibbles::from_nibbles(n)),
+                    node: node.clone(),
+                },
+            )
+            .unwrap();
+        }
+        drop(sc);
+        let mut dc = provider.tx_ref().cursor_dup_write::<tables::StoragesTrie>().unwrap();
+        for n in [[0x2, 0x0, 0x0, 0x0, 0x0, 0x0], [0x4, 0x0, 0x0, 0x0, 0x0, 0x0]] {
+            dc.upsert(
+                hashed_address,
+                &StorageTrieEntry {
+                    nibbles: StoredNibblesSubKey(Nibbles::from_nibbles(n)),
+                    node: node.clone(),
+                },
+            )
+            .unwrap();
+        }
+        drop(dc);
+
+        let f = DatabaseTrieCursorFactory::new(provider.tx_ref());
+        let mut cursor = f.storage_trie_cursor(hashed_address).unwrap();
+
+        let last = cursor.seek_prev(Nibbles::from_nibbles([0xf])).unwrap();
+        let mut results = vec![last.unwrap().0];
+        while let Some((nibbles, _)) = cursor.prev().unwrap() {
+            results.push(nibbles);
+        }
+
+        assert_eq!(results.len(), 5, "expected 5 entries, got {:?}", results);
+        for w in results.windows(2) {
+            assert!(w[0] > w[1], "not descending: {:?} <= {:?}", w[0], w[1]);
+        }
+    }
+
+    #[test]
+    fn split_storage_cursor_seek_prev_no_match_falls_back_to_last_duplicate() {
+        // When `seek_by_key_subkey` finds no subkey `>= key`, the storage
+        // `seek_prev` walks the duplicates forward and returns the last one.
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let node = test_node();
+        let hashed_address = B256::random();
+
+        let mut sc = provider.tx_ref().cursor_dup_write::<tables::StoragesTrieShallow>().unwrap();
+        for n in [[0x4], [0x6], [0x8]] {
+            sc.upsert(
+                hashed_address,
+                &StorageTrieEntry {
+                    nibbles: StoredNibblesSubKey(Nibbles::from_nibbles(n)),
+                    node: node.clone(),
+                },
+            )
+            .unwrap();
+        }
+        drop(sc);
+
+        let f = DatabaseTrieCursorFactory::new(provider.tx_ref());
+        let mut cursor = f.storage_trie_cursor(hashed_address).unwrap();
+
+        // Seek for a key above every stored subkey: falls back to the greatest.
+        let found = cursor.seek_prev(Nibbles::from_nibbles([0x9])).unwrap();
+        assert_eq!(found.unwrap().0, Nibbles::from_nibbles([0x8]));
+    }
+
+    #[test]
+    fn split_storage_cursor_prev_only_shallow() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let node = test_node();
+        let hashed_address = B256::random();
+
+        let mut sc = provider.tx_ref().cursor_dup_write::<tables::StoragesTrieShallow>().unwrap();
+        for n in [[0x1], [0x2]] {
+            sc.upsert(
+                hashed_address,
+                &StorageTrieEntry {
+                    nibbles: StoredNibblesSubKey(Nibbles::from_nibbles(n)),
+                    node: node.clone(),
+                },
+            )
+            .unwrap();
+        }
+        drop(sc);
+
+        let f = DatabaseTrieCursorFactory::new(provider.tx_ref());
+        let mut cursor = f.storage_trie_cursor(hashed_address).unwrap();
+
+        let last = cursor.seek_prev(Nibbles::from_nibbles([0xf])).unwrap();
+        assert_eq!(last.unwrap().0, Nibbles::from_nibbles([0x2]));
+        assert_eq!(cursor.prev().unwrap().unwrap().0, Nibbles::from_nibbles([0x1]));
+        assert!(cursor.prev().unwrap().is_none());
+    }
+
+    #[test]
+    fn split_storage_cursor_prev_only_deep() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let node = test_node();
+        let hashed_address = B256::random();
+
+        let mut dc = provider.tx_ref().cursor_dup_write::<tables::StoragesTrie>().unwrap();
+        for n in [[0x1, 0x0, 0x0, 0x0, 0x0, 0x0], [0x2, 0x0, 0x0, 0x0, 0x0, 0x0]] {
+            dc.upsert(
+                hashed_address,
+                &StorageTrieEntry {
+                    nibbles: StoredNibblesSubKey(Nibbles::from_nibbles(n)),
+                    node: node.clone(),
+                },
+            )
+            .unwrap();
+        }
+        drop(dc);
+
+        let f = DatabaseTrieCursorFactory::new(provider.tx_ref());
+        let mut cursor = f.storage_trie_cursor(hashed_address).unwrap();
+
+        let last = cursor.seek_prev(Nibbles::from_nibbles([0xf])).unwrap();
+        assert_eq!(last.unwrap().0, Nibbles::from_nibbles([0x2, 0x0, 0x0, 0x0, 0x0, 0x0]));
+        assert_eq!(
+            cursor.prev().unwrap().unwrap().0,
+            Nibbles::from_nibbles([0x1, 0x0, 0x0, 0x0, 0x0, 0x0])
+        );
+        assert!(cursor.prev().unwrap().is_none());
+    }
+
+    #[test]
+    fn split_storage_cursor_seek_prev_empty_tables() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let hashed_address = B256::random();
+
+        let f = DatabaseTrieCursorFactory::new(provider.tx_ref());
+        let mut cursor = f.storage_trie_cursor(hashed_address).unwrap();
+
+        assert!(cursor.seek_prev(Nibbles::from_nibbles([0xf])).unwrap().is_none());
+        assert!(cursor.prev().unwrap().is_none());
+    }
+
+    // ---- Randomized equivalence harness ----
+
+    /// Tiny deterministic xorshift RNG so the harness is reproducible.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next_u64(&mut self) -> u64 {
+            let mut x = self.0;
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            self.0 = x;
+            x
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next_u64() % n as u64) as usize
+        }
+
+        fn nibble_key(&mut self) -> Nibbles {
+            // Widely varying lengths, including the depth-5/6 boundary.
+            let len = 1 + self.below(10);
+            let nibbles: Vec<u8> = (0..len).map(|_| self.below(16) as u8).collect();
+            Nibbles::from_nibbles(nibbles)
+        }
+    }
+
+    /// `BTreeMap`-backed reference cursor with single-table semantics.
+    struct ReferenceCursor {
+        map: std::collections::BTreeMap<Nibbles, BranchNodeCompact>,
+        pos: Option<Nibbles>,
+    }
+
+    impl TrieCursor for ReferenceCursor {
+        fn seek_exact(
+            &mut self,
+            key: Nibbles,
+        ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+            Ok(self.map.get(&key).map(|v| {
+                self.pos = Some(key.clone());
+                (key, v.clone())
+            }))
+        }
+
+        fn seek(
+            &mut self,
+            key: Nibbles,
+        ) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+            Ok(self.map.range(key..).next().map(|(k, v)| {
+                self.pos = Some(k.clone());
+                (k.clone(), v.clone())
+            }))
+        }
+
+        fn next(&mut self) -> Result<Option<(Nibbles, BranchNodeCompact)>, DatabaseError> {
+            let Some(current) = self.pos.clone() else { return Ok(None) };
+            use std::ops::Bound::{Excluded, Unbounded};
+            Ok(self.map.range((Excluded(current), Unbounded)).next().map(|(k, v)| {
+                self.pos = Some(k.clone());
+                (k.clone(), v.clone())
+            }))
+        }
+
+        fn current(&mut self) -> Result<Option<Nibbles>, DatabaseError> {
+            Ok(self.pos.clone())
+        }
+
+        fn reset(&mut self) {
+            self.pos = None;
+        }
+    }
+
+    #[test]
+    fn split_cursor_matches_single_table_reference() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let node = test_node();
+
+        let mut rng = Rng(0x5eed_1234_abcd_0001);
+        let mut reference = std::collections::BTreeMap::new();
+
+        let mut shallow =
+            provider.tx_ref().cursor_write::<tables::AccountsTrieShallow>().unwrap();
+        let mut deep = provider.tx_ref().cursor_write::<tables::AccountsTrie>().unwrap();
+
+        for _ in 0..256 {
+            let key = rng.nibble_key();
+            if reference.insert(key.clone(), node.clone()).is_some() {
+                continue; // skip duplicate paths (both sides already agree)
+            }
+            let stored = StoredNibbles(key.clone());
+            if key.len() <= SHALLOW_TRIE_DEPTH {
+                shallow.upsert(stored, &node).unwrap();
+            } else {
+                deep.upsert(stored, &node).unwrap();
+            }
+        }
+        drop(shallow);
+        drop(deep);
+
+        let f = DatabaseTrieCursorFactory::new(provider.tx_ref());
+        let mut split = f.account_trie_cursor().unwrap();
+        let mut refc = ReferenceCursor { map: reference, pos: None };
+
+        // next() is only exercised after a seek() (seek_exact clears the merge
+        // state), so the generator tracks whether iteration is in progress.
+        let mut iterating = false;
+        for _ in 0..2000 {
+            match rng.below(3) {
+                0 => {
+                    let key = rng.nibble_key();
+                    assert_eq!(split.seek(key.clone()).unwrap(), refc.seek(key).unwrap());
+                    iterating = true;
+                }
+                1 => {
+                    let key = rng.nibble_key();
+                    assert_eq!(
+                        split.seek_exact(key.clone()).unwrap(),
+                        refc.seek_exact(key).unwrap()
+                    );
+                    iterating = false;
+                }
+                _ => {
+                    if iterating {
+                        let a = split.next().unwrap();
+                        let b = refc.next().unwrap();
+                        assert_eq!(a, b);
+                        iterating = b.is_some();
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn split_cursor_reverse_matches_single_table_reference() {
+        // Descending analogue of `split_cursor_matches_single_table_reference`:
+        // randomized `seek_prev` + `prev` against a `BTreeMap` reference, so the
+        // account cursor's `seek_prev` fallbacks and the two-way `consume_larger`
+        // merge are checked for exact equivalence, not just hand-picked cases.
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let node = test_node();
+
+        let mut rng = Rng(0x5eed_dead_beef_0002);
+        let mut reference = std::collections::BTreeMap::new();
+
+        let mut shallow =
+            provider.tx_ref().cursor_write::<tables::AccountsTrieShallow>().unwrap();
+        let mut deep = provider.tx_ref().cursor_write::<tables::AccountsTrie>().unwrap();
+        for _ in 0..256 {
+            let key = rng.nibble_key();
+            if reference.insert(key.clone(), node.clone()).is_some() {
+                continue;
+            }
+            let stored = StoredNibbles(key.clone());
+            if key.len() <= SHALLOW_TRIE_DEPTH {
+                shallow.upsert(stored, &node).unwrap();
+            } else {
+                deep.upsert(stored, &node).unwrap();
+            }
+        }
+        drop(shallow);
+        drop(deep);
+
+        let f = DatabaseTrieCursorFactory::new(provider.tx_ref());
+        let mut split = f.account_trie_cursor().unwrap();
+
+        // Reference descending helpers over the `BTreeMap`.
+        let ref_seek_prev = |key: &Nibbles| -> Option<Nibbles> {
+            reference.range(..=key.clone()).next_back().map(|(k, _)| k.clone())
+        };
+        let ref_prev = |pos: &Nibbles| -> Option<Nibbles> {
+            use std::ops::Bound::{Excluded, Unbounded};
+            reference.range((Unbounded, Excluded(pos.clone()))).next_back().map(|(k, _)| k.clone())
+        };
+
+        let mut pos: Option<Nibbles> = None;
+        for _ in 0..2000 {
+            if pos.is_none() || rng.below(3) == 0 {
+                let key = rng.nibble_key();
+                let got = split.seek_prev(key.clone()).unwrap().map(|(k, _)| k);
+                let expected = ref_seek_prev(&key);
+                assert_eq!(got, expected);
+                pos = expected;
+            } else {
+                let current = pos.clone().unwrap();
+                let got = split.prev().unwrap().map(|(k, _)| k);
+                let expected = ref_prev(&current);
+                assert_eq!(got, expected);
+                pos = expected;
+            }
+        }
+    }
+
     #[test]
     fn split_cursor_only_deep_entries() {
         let factory = create_test_provider_factory();
@@ -0,0 +1,154 @@
+// =============================================================================
+// Prefix-bounded range iteration across the split tables.
+//
+// The cursor-level analogue of the classic TrieDB node iterator: walk all merged
+// `(Nibbles, BranchNodeCompact)` entries under a nibble prefix (or within a
+// range) in sorted order, *through* the shallow/deep merge. This is the natural
+// partitioning primitive for parallel subtrie traversal — enumerate the shallow
+// branch nodes at depth `SHALLOW_TRIE_DEPTH`, then spawn one `walk_prefix` per
+// populated child prefix to traverse deep subtries independently.
+//
+// A prefix shorter than `SHALLOW_TRIE_DEPTH` still surfaces deep descendants,
+// because the underlying split cursor merges both tables.
+// =============================================================================
+
+use std::ops::{Bound, RangeBounds};
+
+use reth_db_api::DatabaseError;
+use reth_trie::{trie_cursor::TrieCursor, BranchNodeCompact, Nibbles};
+
+/// Extension methods adding range/prefix iteration to any [`TrieCursor`],
+/// including the split account and storage cursors.
+pub trait TrieCursorWalkExt: TrieCursor + Sized {
+    /// Iterate all entries whose path starts with `prefix`, in ascending order.
+    fn walk_prefix(&mut self, prefix: Nibbles) -> PrefixWalk<'_, Self> {
+        PrefixWalk { cursor: self, prefix, started: false, done: false }
+    }
+
+    /// Iterate all merged entries within `range`, in ascending order.
+    fn walk_range(&mut self, range: impl RangeBounds<Nibbles>) -> RangeWalk<'_, Self> {
+        let start = match range.start_bound() {
+            Bound::Included(k) => Some(k.clone()),
+            // Nibble keys are discrete; there is no generic "successor", so an
+            // excluded start is handled by skipping the first match below.
+            Bound::Excluded(k) => Some(k.clone()),
+            Bound::Unbounded => None,
+        };
+        let skip_start = matches!(range.start_bound(), Bound::Excluded(_));
+        let end = match range.end_bound() {
+            Bound::Included(k) => Bound::Included(k.clone()),
+            Bound::Excluded(k) => Bound::Excluded(k.clone()),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        RangeWalk { cursor: self, start, skip_start, end, started: false, done: false }
+    }
+}
+
+impl<C: TrieCursor + Sized> TrieCursorWalkExt for C {}
+
+fn starts_with(key: &Nibbles, prefix: &Nibbles) -> bool {
+    key.len() >= prefix.len() && key.slice(..prefix.len()) == *prefix
+}
+
+/// Iterator yielding merged entries under a common prefix.
+#[derive(Debug)]
+pub struct PrefixWalk<'c, C> {
+    cursor: &'c mut C,
+    prefix: Nibbles,
+    started: bool,
+    done: bool,
+}
+
+impl<C: TrieCursor> Iterator for PrefixWalk<'_, C> {
+    type Item = Result<(Nibbles, BranchNodeCompact), DatabaseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let step = if self.started {
+            self.cursor.next()
+        } else {
+            self.started = true;
+            self.cursor.seek(self.prefix.clone())
+        };
+        match step {
+            Ok(Some((key, node))) if starts_with(&key, &self.prefix) => Some(Ok((key, node))),
+            Ok(_) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Iterator yielding merged entries within a key range.
+#[derive(Debug)]
+pub struct RangeWalk<'c, C> {
+    cursor: &'c mut C,
+    start: Option<Nibbles>,
+    skip_start: bool,
+    end: Bound<Nibbles>,
+    started: bool,
+    done: bool,
+}
+
+impl<C: TrieCursor> RangeWalk<'_, C> {
+    fn in_range(&self, key: &Nibbles) -> bool {
+        match &self.end {
+            Bound::Included(e) => key <= e,
+            Bound::Excluded(e) => key < e,
+            Bound::Unbounded => true,
+        }
+    }
+}
+
+impl<C: TrieCursor> Iterator for RangeWalk<'_, C> {
+    type Item = Result<(Nibbles, BranchNodeCompact), DatabaseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let step = if self.started {
+            self.cursor.next()
+        } else {
+            self.started = true;
+            match self.start.clone() {
+                Some(start) => {
+                    let first = self.cursor.seek(start.clone());
+                    // Drop an excluded start key if it matched exactly.
+                    if self.skip_start {
+                        if let Ok(Some((key, _))) = &first {
+                            if *key == start {
+                                self.cursor.next()
+                            } else {
+                                first
+                            }
+                        } else {
+                            first
+                        }
+                    } else {
+                        first
+                    }
+                }
+                None => self.cursor.seek(Nibbles::default()),
+            }
+        };
+        match step {
+            Ok(Some((key, node))) if self.in_range(&key) => Some(Ok((key, node))),
+            Ok(_) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
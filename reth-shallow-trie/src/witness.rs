@@ -0,0 +1,70 @@
+// =============================================================================
+// Stateless witness cursor factory.
+//
+// For stateless validation we often hold the top of the trie (a witness of the
+// shallow branch nodes) but not the full deep tables. This factory — the
+// split-table analogue of reth's `NoopTrieCursorFactory` — answers cursor reads
+// purely from an in-memory shallow map, with the deep side permanently empty,
+// so callers get a DB-free cursor for partial-trie / stateless scenarios and
+// unit tests that don't want a real `ProviderFactory`.
+//
+// Target file (reth): `crates/trie/trie/src/trie_cursor/noop.rs`.
+// =============================================================================
+
+use alloy_primitives::B256;
+use std::collections::BTreeMap;
+
+use reth_db_api::DatabaseError;
+use reth_trie::{
+    trie_cursor::TrieCursorFactory, BranchNodeCompact, StoredNibbles, StoredNibblesSubKey,
+};
+use reth_trie_common::constants::SHALLOW_TRIE_DEPTH;
+
+use crate::shallow_mem::{ShallowMemAccountCursor, ShallowMemStorageCursor, ShallowTrieMem};
+
+/// A [`TrieCursorFactory`] backed solely by a preloaded shallow trie. Keys
+/// deeper than `SHALLOW_TRIE_DEPTH` are absent from the witness, so exact
+/// lookups for them return `None`.
+#[derive(Debug, Default, Clone)]
+pub struct WitnessSplitTrieCursorFactory {
+    shallow: ShallowTrieMem,
+}
+
+impl WitnessSplitTrieCursorFactory {
+    /// Build a witness factory from pre-sorted shallow account and storage maps.
+    /// Entries deeper than `SHALLOW_TRIE_DEPTH` are dropped.
+    pub fn new(
+        mut accounts: BTreeMap<StoredNibbles, BranchNodeCompact>,
+        mut storages: BTreeMap<B256, BTreeMap<StoredNibblesSubKey, BranchNodeCompact>>,
+    ) -> Self {
+        accounts.retain(|k, _| k.0.len() <= SHALLOW_TRIE_DEPTH);
+        for nodes in storages.values_mut() {
+            nodes.retain(|k, _| k.0.len() <= SHALLOW_TRIE_DEPTH);
+        }
+        storages.retain(|_, nodes| !nodes.is_empty());
+        Self { shallow: ShallowTrieMem::from_maps(accounts, storages) }
+    }
+}
+
+impl TrieCursorFactory for WitnessSplitTrieCursorFactory {
+    type AccountTrieCursor<'a>
+        = ShallowMemAccountCursor<'a>
+    where
+        Self: 'a;
+
+    type StorageTrieCursor<'a>
+        = ShallowMemStorageCursor<'a>
+    where
+        Self: 'a;
+
+    fn account_trie_cursor(&self) -> Result<Self::AccountTrieCursor<'_>, DatabaseError> {
+        Ok(self.shallow.account_cursor())
+    }
+
+    fn storage_trie_cursor(
+        &self,
+        hashed_address: B256,
+    ) -> Result<Self::StorageTrieCursor<'_>, DatabaseError> {
+        Ok(self.shallow.storage_cursor(hashed_address))
+    }
+}
@@ -34,6 +34,19 @@ use alloy_primitives::B256;
 pub fn write_account_trie_updates_split<TX: DbTxMut>(
     tx: &TX,
     updates: &TrieUpdatesSorted,
+) -> Result<usize, DatabaseError> {
+    write_account_trie_updates_split_with_depth(tx, updates, SHALLOW_TRIE_DEPTH, None)
+}
+
+/// [`write_account_trie_updates_split`] with the shallow/deep boundary supplied
+/// as a runtime policy rather than the compile-time [`SHALLOW_TRIE_DEPTH`]
+/// constant, plus an opt-in `profile` that tallies a node-path-length
+/// histogram as nodes are written.
+pub fn write_account_trie_updates_split_with_depth<TX: DbTxMut>(
+    tx: &TX,
+    updates: &TrieUpdatesSorted,
+    shallow_trie_depth: usize,
+    mut profile: Option<&mut crate::metrics::DepthHistogram>,
 ) -> Result<usize, DatabaseError> {
     let mut shallow_cursor = tx.cursor_write::<tables::AccountsTrieShallow>()?;
     let mut deep_cursor = tx.cursor_write::<tables::AccountsTrie>()?;
@@ -47,7 +60,11 @@ pub fn write_account_trie_updates_split<TX: DbTxMut>(
         num_entries += 1;
         let stored = StoredNibbles(nibbles.clone());
 
-        if nibbles.len() <= SHALLOW_TRIE_DEPTH {
+        if let (Some(profile), Some(node)) = (profile.as_deref_mut(), maybe_node) {
+            profile.observe(nibbles.len(), node);
+        }
+
+        if nibbles.len() <= shallow_trie_depth {
             // --- Shallow table ---
             // Delete old entry if it exists.
             if shallow_cursor.seek_exact(stored.clone())?.is_some() {
@@ -84,6 +101,24 @@ pub fn write_storage_trie_updates_split<TX: DbTxMut>(
     tx: &TX,
     hashed_address: B256,
     updates: &StorageTrieUpdatesSorted,
+) -> Result<usize, DatabaseError> {
+    write_storage_trie_updates_split_with_depth(
+        tx,
+        hashed_address,
+        updates,
+        SHALLOW_TRIE_DEPTH,
+        None,
+    )
+}
+
+/// [`write_storage_trie_updates_split`] with a runtime shallow/deep boundary
+/// and an opt-in node-path-length `profile`.
+pub fn write_storage_trie_updates_split_with_depth<TX: DbTxMut>(
+    tx: &TX,
+    hashed_address: B256,
+    updates: &StorageTrieUpdatesSorted,
+    shallow_trie_depth: usize,
+    mut profile: Option<&mut crate::metrics::DepthHistogram>,
 ) -> Result<usize, DatabaseError> {
     let mut shallow_cursor = tx.cursor_dup_write::<tables::StoragesTrieShallow>()?;
     let mut deep_cursor = tx.cursor_dup_write::<tables::StoragesTrie>()?;
@@ -104,7 +139,11 @@ pub fn write_storage_trie_updates_split<TX: DbTxMut>(
         num_entries += 1;
         let stored_nibbles = StoredNibblesSubKey(*nibbles);
 
-        if nibbles.len() <= SHALLOW_TRIE_DEPTH {
+        if let (Some(profile), Some(node)) = (profile.as_deref_mut(), maybe_node) {
+            profile.observe(nibbles.len(), node);
+        }
+
+        if nibbles.len() <= shallow_trie_depth {
             // --- Shallow table ---
             if shallow_cursor
                 .seek_by_key_subkey(hashed_address, stored_nibbles.clone())?
@@ -140,13 +179,491 @@ pub fn write_storage_trie_updates_split<TX: DbTxMut>(
     Ok(num_entries)
 }
 
+// =============================================================================
+// Cache-consistent write routing
+//
+// Variants of the routers above that update a resident `ShallowTrieMem` in
+// lockstep with the DB writes, so the in-RAM shallow cache never drifts from
+// the committed tables.
+// =============================================================================
+
+use crate::shallow_mem::ShallowTrieMem;
+
+/// Like [`write_account_trie_updates_split`], but also mirrors every shallow
+/// node into `mem` so the resident cache stays consistent.
+pub fn write_account_trie_updates_split_cached<TX: DbTxMut>(
+    tx: &TX,
+    updates: &TrieUpdatesSorted,
+    mem: &mut ShallowTrieMem,
+) -> Result<usize, DatabaseError> {
+    let mut shallow_cursor = tx.cursor_write::<tables::AccountsTrieShallow>()?;
+    let mut deep_cursor = tx.cursor_write::<tables::AccountsTrie>()?;
+    let mut num_entries = 0;
+
+    for (nibbles, maybe_node) in &updates.account_nodes {
+        if nibbles.is_empty() {
+            continue;
+        }
+
+        num_entries += 1;
+        let stored = StoredNibbles(nibbles.clone());
+
+        if nibbles.len() <= SHALLOW_TRIE_DEPTH {
+            if shallow_cursor.seek_exact(stored.clone())?.is_some() {
+                shallow_cursor.delete_current()?;
+            }
+            if let Some(node) = maybe_node {
+                shallow_cursor.upsert(stored, node)?;
+            }
+            // Keep the resident cache in lockstep (None removes the key).
+            mem.set_account(nibbles, maybe_node.as_ref());
+        } else {
+            if deep_cursor.seek_exact(stored.clone())?.is_some() {
+                deep_cursor.delete_current()?;
+            }
+            if let Some(node) = maybe_node {
+                deep_cursor.upsert(stored, node)?;
+            }
+        }
+    }
+
+    Ok(num_entries)
+}
+
+/// Like [`write_storage_trie_updates_split`], but also mirrors every shallow
+/// node into `mem`. A full `is_deleted()` clear drops the whole per-address
+/// map from the cache.
+pub fn write_storage_trie_updates_split_cached<TX: DbTxMut>(
+    tx: &TX,
+    hashed_address: B256,
+    updates: &StorageTrieUpdatesSorted,
+    mem: &mut ShallowTrieMem,
+) -> Result<usize, DatabaseError> {
+    let mut shallow_cursor = tx.cursor_dup_write::<tables::StoragesTrieShallow>()?;
+    let mut deep_cursor = tx.cursor_dup_write::<tables::StoragesTrie>()?;
+
+    if updates.is_deleted() {
+        if shallow_cursor.seek_exact(hashed_address)?.is_some() {
+            shallow_cursor.delete_current_duplicates()?;
+        }
+        if deep_cursor.seek_exact(hashed_address)?.is_some() {
+            deep_cursor.delete_current_duplicates()?;
+        }
+        mem.clear_storage(hashed_address);
+    }
+
+    let mut num_entries = 0;
+
+    for (nibbles, maybe_node) in updates.storage_nodes.iter().filter(|(n, _)| !n.is_empty()) {
+        num_entries += 1;
+        let stored_nibbles = StoredNibblesSubKey(*nibbles);
+
+        if nibbles.len() <= SHALLOW_TRIE_DEPTH {
+            if shallow_cursor
+                .seek_by_key_subkey(hashed_address, stored_nibbles.clone())?
+                .filter(|e| e.nibbles == stored_nibbles)
+                .is_some()
+            {
+                shallow_cursor.delete_current()?;
+            }
+            if let Some(node) = maybe_node {
+                shallow_cursor.upsert(
+                    hashed_address,
+                    &StorageTrieEntry { nibbles: stored_nibbles, node: node.clone() },
+                )?;
+            }
+            mem.set_storage(hashed_address, nibbles, maybe_node.as_ref());
+        } else {
+            if deep_cursor
+                .seek_by_key_subkey(hashed_address, stored_nibbles.clone())?
+                .filter(|e| e.nibbles == stored_nibbles)
+                .is_some()
+            {
+                deep_cursor.delete_current()?;
+            }
+            if let Some(node) = maybe_node {
+                deep_cursor.upsert(
+                    hashed_address,
+                    &StorageTrieEntry { nibbles: stored_nibbles, node: node.clone() },
+                )?;
+            }
+        }
+    }
+
+    Ok(num_entries)
+}
+
+// =============================================================================
+// LRU-cache-consistent write routing
+//
+// Variants that keep a `ShallowTrieCache` in lockstep with the committed
+// tables: every shallow overwrite refreshes the cached node and every deletion
+// evicts it, so the request's "invalidation must hook the write path" holds.
+// =============================================================================
+
+use crate::cache::ShallowTrieCache;
+
+/// Like [`write_account_trie_updates_split`], but write-through to `cache`:
+/// each shallow insert refreshes the cached node and each deletion evicts it.
+pub fn write_account_trie_updates_split_cached_lru<TX: DbTxMut>(
+    tx: &TX,
+    updates: &TrieUpdatesSorted,
+    cache: &ShallowTrieCache,
+) -> Result<usize, DatabaseError> {
+    let mut shallow_cursor = tx.cursor_write::<tables::AccountsTrieShallow>()?;
+    let mut deep_cursor = tx.cursor_write::<tables::AccountsTrie>()?;
+    let mut num_entries = 0;
+
+    for (nibbles, maybe_node) in &updates.account_nodes {
+        if nibbles.is_empty() {
+            continue;
+        }
+
+        num_entries += 1;
+        let stored = StoredNibbles(nibbles.clone());
+
+        if nibbles.len() <= SHALLOW_TRIE_DEPTH {
+            if shallow_cursor.seek_exact(stored.clone())?.is_some() {
+                shallow_cursor.delete_current()?;
+            }
+            match maybe_node {
+                Some(node) => {
+                    shallow_cursor.upsert(stored.clone(), node)?;
+                    // Write-through: refresh the cached node.
+                    cache.put_account(stored, node.clone());
+                }
+                // Deletion evicts the cached node.
+                None => cache.invalidate_account(&stored),
+            }
+        } else {
+            if deep_cursor.seek_exact(stored.clone())?.is_some() {
+                deep_cursor.delete_current()?;
+            }
+            if let Some(node) = maybe_node {
+                deep_cursor.upsert(stored, node)?;
+            }
+        }
+    }
+
+    Ok(num_entries)
+}
+
+/// Like [`write_storage_trie_updates_split`], but write-through to `cache`. A
+/// full `is_deleted()` clear evicts the account's whole cache group.
+pub fn write_storage_trie_updates_split_cached_lru<TX: DbTxMut>(
+    tx: &TX,
+    hashed_address: B256,
+    updates: &StorageTrieUpdatesSorted,
+    cache: &ShallowTrieCache,
+) -> Result<usize, DatabaseError> {
+    let mut shallow_cursor = tx.cursor_dup_write::<tables::StoragesTrieShallow>()?;
+    let mut deep_cursor = tx.cursor_dup_write::<tables::StoragesTrie>()?;
+
+    if updates.is_deleted() {
+        if shallow_cursor.seek_exact(hashed_address)?.is_some() {
+            shallow_cursor.delete_current_duplicates()?;
+        }
+        if deep_cursor.seek_exact(hashed_address)?.is_some() {
+            deep_cursor.delete_current_duplicates()?;
+        }
+        cache.invalidate_storage_account(hashed_address);
+    }
+
+    let mut num_entries = 0;
+
+    for (nibbles, maybe_node) in updates.storage_nodes.iter().filter(|(n, _)| !n.is_empty()) {
+        num_entries += 1;
+        let stored_nibbles = StoredNibblesSubKey(*nibbles);
+
+        if nibbles.len() <= SHALLOW_TRIE_DEPTH {
+            if shallow_cursor
+                .seek_by_key_subkey(hashed_address, stored_nibbles.clone())?
+                .filter(|e| e.nibbles == stored_nibbles)
+                .is_some()
+            {
+                shallow_cursor.delete_current()?;
+            }
+            match maybe_node {
+                Some(node) => {
+                    shallow_cursor.upsert(
+                        hashed_address,
+                        &StorageTrieEntry { nibbles: stored_nibbles.clone(), node: node.clone() },
+                    )?;
+                    cache.put_storage(hashed_address, stored_nibbles, node.clone());
+                }
+                None => cache.invalidate_storage(hashed_address, &stored_nibbles),
+            }
+        } else {
+            if deep_cursor
+                .seek_by_key_subkey(hashed_address, stored_nibbles.clone())?
+                .filter(|e| e.nibbles == stored_nibbles)
+                .is_some()
+            {
+                deep_cursor.delete_current()?;
+            }
+            if let Some(node) = maybe_node {
+                deep_cursor.upsert(
+                    hashed_address,
+                    &StorageTrieEntry { nibbles: stored_nibbles, node: node.clone() },
+                )?;
+            }
+        }
+    }
+
+    Ok(num_entries)
+}
+
+// =============================================================================
+// History-recording write routing
+//
+// Variants that capture the pre-image of every overwritten or deleted node
+// into the `*TrieHistory` tables (keyed by `block`) before it is replaced, so a
+// retained revision can be reconstructed later. See [`crate::history`].
+// =============================================================================
+
+use crate::history::{record_account_pre_image, record_storage_pre_image};
+
+/// Like [`write_account_trie_updates_split`], but records each overwritten or
+/// deleted node's pre-image under `block` before replacing it.
+pub fn write_account_trie_updates_split_versioned<TX: DbTxMut>(
+    tx: &TX,
+    block: u64,
+    updates: &TrieUpdatesSorted,
+) -> Result<usize, DatabaseError> {
+    let mut shallow_cursor = tx.cursor_write::<tables::AccountsTrieShallow>()?;
+    let mut deep_cursor = tx.cursor_write::<tables::AccountsTrie>()?;
+    let mut num_entries = 0;
+
+    for (nibbles, maybe_node) in &updates.account_nodes {
+        if nibbles.is_empty() {
+            continue;
+        }
+
+        num_entries += 1;
+        let stored = StoredNibbles(nibbles.clone());
+
+        if nibbles.len() <= SHALLOW_TRIE_DEPTH {
+            if let Some((_, old)) = shallow_cursor.seek_exact(stored.clone())? {
+                record_account_pre_image(tx, block, nibbles, &old)?;
+                shallow_cursor.delete_current()?;
+            }
+            if let Some(node) = maybe_node {
+                shallow_cursor.upsert(stored, node)?;
+            }
+        } else if let Some((_, old)) = deep_cursor.seek_exact(stored.clone())? {
+            record_account_pre_image(tx, block, nibbles, &old)?;
+            deep_cursor.delete_current()?;
+            if let Some(node) = maybe_node {
+                deep_cursor.upsert(stored, node)?;
+            }
+        } else if let Some(node) = maybe_node {
+            deep_cursor.upsert(stored, node)?;
+        }
+    }
+
+    Ok(num_entries)
+}
+
+/// Like [`write_storage_trie_updates_split`], but records each overwritten or
+/// deleted node's pre-image under `block` before replacing it.
+pub fn write_storage_trie_updates_split_versioned<TX: DbTxMut>(
+    tx: &TX,
+    block: u64,
+    hashed_address: B256,
+    updates: &StorageTrieUpdatesSorted,
+) -> Result<usize, DatabaseError> {
+    let mut shallow_cursor = tx.cursor_dup_write::<tables::StoragesTrieShallow>()?;
+    let mut deep_cursor = tx.cursor_dup_write::<tables::StoragesTrie>()?;
+
+    if updates.is_deleted() {
+        if let Some(entry) = shallow_cursor.seek_exact(hashed_address)? {
+            record_storage_pre_image(tx, block, hashed_address, &entry.1.nibbles.0, &entry.1.node)?;
+            while let Some(dup) = shallow_cursor.next_dup()? {
+                record_storage_pre_image(tx, block, hashed_address, &dup.1.nibbles.0, &dup.1.node)?;
+            }
+            shallow_cursor.seek_exact(hashed_address)?;
+            shallow_cursor.delete_current_duplicates()?;
+        }
+        if let Some(entry) = deep_cursor.seek_exact(hashed_address)? {
+            record_storage_pre_image(tx, block, hashed_address, &entry.1.nibbles.0, &entry.1.node)?;
+            while let Some(dup) = deep_cursor.next_dup()? {
+                record_storage_pre_image(tx, block, hashed_address, &dup.1.nibbles.0, &dup.1.node)?;
+            }
+            deep_cursor.seek_exact(hashed_address)?;
+            deep_cursor.delete_current_duplicates()?;
+        }
+    }
+
+    let mut num_entries = 0;
+
+    for (nibbles, maybe_node) in updates.storage_nodes.iter().filter(|(n, _)| !n.is_empty()) {
+        num_entries += 1;
+        let stored_nibbles = StoredNibblesSubKey(*nibbles);
+
+        if nibbles.len() <= SHALLOW_TRIE_DEPTH {
+            if let Some(old) = shallow_cursor
+                .seek_by_key_subkey(hashed_address, stored_nibbles.clone())?
+                .filter(|e| e.nibbles == stored_nibbles)
+            {
+                record_storage_pre_image(tx, block, hashed_address, nibbles, &old.node)?;
+                shallow_cursor.delete_current()?;
+            }
+            if let Some(node) = maybe_node {
+                shallow_cursor.upsert(
+                    hashed_address,
+                    &StorageTrieEntry { nibbles: stored_nibbles, node: node.clone() },
+                )?;
+            }
+        } else {
+            if let Some(old) = deep_cursor
+                .seek_by_key_subkey(hashed_address, stored_nibbles.clone())?
+                .filter(|e| e.nibbles == stored_nibbles)
+            {
+                record_storage_pre_image(tx, block, hashed_address, nibbles, &old.node)?;
+                deep_cursor.delete_current()?;
+            }
+            if let Some(node) = maybe_node {
+                deep_cursor.upsert(
+                    hashed_address,
+                    &StorageTrieEntry { nibbles: stored_nibbles, node: node.clone() },
+                )?;
+            }
+        }
+    }
+
+    Ok(num_entries)
+}
+
+// =============================================================================
+// Parallel storage trie flush
+//
+// On large blocks the per-account storage-trie flush dominates commit time.
+// Following reth's parallel-storage-root approach, the CPU-bound part — routing
+// each node to the shallow/deep table and cloning it into an owned row — is
+// fanned out across a rayon pool, while the DB writes stay on the
+// single writer thread (MDBX requires single-writer cursor access).
+// =============================================================================
+
+use rayon::prelude::*;
+
+/// A node prepared off-thread, ready for a cursor `upsert`/`delete`.
+#[derive(Debug)]
+struct PreparedStorageRow {
+    key: StoredNibblesSubKey,
+    /// `None` marks a deletion of the node at `key`.
+    value: Option<BranchNodeCompact>,
+    /// Whether the row belongs in the shallow table.
+    is_shallow: bool,
+}
+
+/// Per-account batch of prepared rows, in hashed-address order.
+#[derive(Debug)]
+struct PreparedStorageBatch {
+    hashed_address: B256,
+    is_deleted: bool,
+    rows: Vec<PreparedStorageRow>,
+}
+
+/// Flush storage trie updates for many accounts, parallelizing the per-node
+/// routing work (classifying and cloning each node) while keeping the actual DB
+/// writes serial.
+///
+/// Returns the aggregated number of node entries written across all accounts.
+pub fn write_all_storage_trie_updates_parallel<TX: DbTxMut>(
+    tx: &TX,
+    updates: &std::collections::BTreeMap<B256, StorageTrieUpdatesSorted>,
+) -> Result<usize, DatabaseError> {
+    // CPU-bound phase: classify and clone each node into an owned buffer.
+    let mut batches: Vec<PreparedStorageBatch> = updates
+        .par_iter()
+        .map(|(hashed_address, updates)| {
+            let rows = updates
+                .storage_nodes
+                .iter()
+                .filter(|(n, _)| !n.is_empty())
+                .map(|(nibbles, maybe_node)| PreparedStorageRow {
+                    key: StoredNibblesSubKey(*nibbles),
+                    value: maybe_node.clone(),
+                    is_shallow: nibbles.len() <= SHALLOW_TRIE_DEPTH,
+                })
+                .collect();
+            PreparedStorageBatch {
+                hashed_address: *hashed_address,
+                is_deleted: updates.is_deleted(),
+                rows,
+            }
+        })
+        .collect();
+
+    // `par_iter()` over a BTreeMap preserves input order, but sort defensively
+    // so cursor writes proceed in ascending hashed-address order.
+    batches.sort_unstable_by_key(|b| b.hashed_address);
+
+    // Serial phase: single-writer cursor access into the two dup-sorted tables.
+    let mut shallow_cursor = tx.cursor_dup_write::<tables::StoragesTrieShallow>()?;
+    let mut deep_cursor = tx.cursor_dup_write::<tables::StoragesTrie>()?;
+    let mut num_entries = 0;
+
+    for batch in batches {
+        let hashed_address = batch.hashed_address;
+        if batch.is_deleted {
+            if shallow_cursor.seek_exact(hashed_address)?.is_some() {
+                shallow_cursor.delete_current_duplicates()?;
+            }
+            if deep_cursor.seek_exact(hashed_address)?.is_some() {
+                deep_cursor.delete_current_duplicates()?;
+            }
+        }
+
+        for row in batch.rows {
+            num_entries += 1;
+            // The two tables have distinct cursor types, so each branch owns
+            // its seek/delete/upsert against the correct dup-sorted table.
+            if row.is_shallow {
+                if shallow_cursor
+                    .seek_by_key_subkey(hashed_address, row.key.clone())?
+                    .filter(|e| e.nibbles == row.key)
+                    .is_some()
+                {
+                    shallow_cursor.delete_current()?;
+                }
+                if let Some(node) = row.value {
+                    shallow_cursor
+                        .upsert(hashed_address, &StorageTrieEntry { nibbles: row.key, node })?;
+                }
+            } else {
+                if deep_cursor
+                    .seek_by_key_subkey(hashed_address, row.key.clone())?
+                    .filter(|e| e.nibbles == row.key)
+                    .is_some()
+                {
+                    deep_cursor.delete_current()?;
+                }
+                if let Some(node) = row.value {
+                    deep_cursor
+                        .upsert(hashed_address, &StorageTrieEntry { nibbles: row.key, node })?;
+                }
+            }
+        }
+    }
+
+    Ok(num_entries)
+}
+
 // =============================================================================
 // Clear helpers (for merkle stage full rebuild)
 // =============================================================================
 
 /// Clear both shallow and deep account trie tables.
 /// Replaces `tx.clear::<tables::AccountsTrie>()?` in the merkle stage.
-pub fn clear_account_trie_tables<TX: DbTxMut>(tx: &TX) -> Result<(), DatabaseError> {
+///
+/// Takes `_shallow_trie_depth` so the clear helpers share a single
+/// depth-policy signature with the writers; a full rebuild drops both tiers
+/// regardless of the boundary, so the value is not consulted.
+pub fn clear_account_trie_tables<TX: DbTxMut>(
+    tx: &TX,
+    _shallow_trie_depth: usize,
+) -> Result<(), DatabaseError> {
     tx.clear::<tables::AccountsTrieShallow>()?;
     tx.clear::<tables::AccountsTrie>()?;
     Ok(())
@@ -154,7 +671,12 @@ pub fn clear_account_trie_tables<TX: DbTxMut>(tx: &TX) -> Result<(), DatabaseErr
 
 /// Clear both shallow and deep storage trie tables.
 /// Replaces `tx.clear::<tables::StoragesTrie>()?` in the merkle stage.
-pub fn clear_storage_trie_tables<TX: DbTxMut>(tx: &TX) -> Result<(), DatabaseError> {
+///
+/// See [`clear_account_trie_tables`] for why the depth is accepted but unused.
+pub fn clear_storage_trie_tables<TX: DbTxMut>(
+    tx: &TX,
+    _shallow_trie_depth: usize,
+) -> Result<(), DatabaseError> {
     tx.clear::<tables::StoragesTrieShallow>()?;
     tx.clear::<tables::StoragesTrie>()?;
     Ok(())